@@ -0,0 +1,116 @@
+use anyhow::Context;
+use axum::extract::ws::Message;
+use serde::Serialize;
+use std::{
+    path::Path,
+    time::Duration,
+};
+use tokio::{
+    io::{
+        AsyncWriteExt,
+        BufWriter,
+    },
+    sync::mpsc,
+};
+use tracing::{ event, Level };
+
+use crate::timestamp::Timestamp;
+
+/// How often the captured frames are flushed to the file.
+const CAPTURE_FLUSH_INTERVAL: Duration = Duration::from_secs(1);
+
+/// The CapturedFrame structure is one line of the capture file, recording
+/// a frame received from a WebSocket client.  Binary payloads are written
+/// in hex.
+///
+/// `{"timestamp":"...","connectionId":"...","type":"text","payload":"..."}`
+#[derive(Serialize)]
+struct CapturedFrame<'a> {
+    timestamp:      Timestamp,
+
+    #[serde(rename = "connectionId")]
+    connection_id:  &'a str,
+
+    r#type:         &'static str,
+    payload:        String,
+}
+
+/// This function opens the capture file for appending, creating it if it
+/// doesn't exist.
+pub fn open_capture_file(path: &Path) -> Result<std::fs::File, anyhow::Error> {
+    std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .with_context(|| format!("Unable to open the capture file {}", path.display()))
+}
+
+/// The CaptureWriter structure appends the frames received from WebSocket
+/// clients to the capture file.  Recording a frame only queues it, and a
+/// background task does the writing, so a slow disk never holds up a
+/// connection.
+#[derive(Clone)]
+pub struct CaptureWriter {
+    sender: mpsc::UnboundedSender<String>,
+}
+
+impl CaptureWriter {
+    /// This function opens the capture file for appending, and starts the
+    /// task writing to it.
+    pub fn open(path: &Path) -> Result<CaptureWriter, anyhow::Error> {
+        let file = open_capture_file(path)?;
+
+        let (sender, receiver) = mpsc::unbounded_channel();
+        tokio::spawn(write_captured_frames(tokio::fs::File::from_std(file), receiver));
+
+        Ok(CaptureWriter { sender })
+    }
+
+    /// This method queues a frame received on the given connection.  Only
+    /// text and binary frames are captured.
+    pub fn record(&self, connection_id: &str, message: &Message) {
+        let (r#type, payload) = match message {
+            Message::Text(text) => ("text", text.clone()),
+            Message::Binary(data) => ("binary", hex::encode(data)),
+            _ => return,
+        };
+
+        let frame = CapturedFrame {
+            timestamp:  Timestamp::now(),
+            connection_id,
+            r#type,
+            payload,
+        };
+
+        // The writer only stops if the file can't be written, which has
+        // already been logged.
+        let _ = self.sender.send(serde_json::to_string(&frame).unwrap());
+    }
+} // end CaptureWriter
+
+/// This function writes the queued lines to the capture file, flushing
+/// them periodically rather than after every line.
+async fn write_captured_frames(
+    file:           tokio::fs::File,
+    mut receiver:   mpsc::UnboundedReceiver<String>,
+) {
+    let mut writer = BufWriter::new(file);
+    let mut flush_interval = tokio::time::interval(CAPTURE_FLUSH_INTERVAL);
+
+    loop {
+        let result = tokio::select! {
+            line = receiver.recv() => match line {
+                Some(line) => writer.write_all(format!("{}\n", line).as_bytes()).await,
+                None => break,
+            },
+            _ = flush_interval.tick() => writer.flush().await,
+        };
+
+        if let Err(e) = result {
+            event!(Level::ERROR, "Error - could not write to the capture file: {}", e);
+            return;
+        }
+    }
+
+    let _ = writer.flush().await;
+} // end write_captured_frames
@@ -0,0 +1,78 @@
+use hmac::{
+    Hmac,
+    Mac,
+};
+use sha2::Sha256;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// The number of bytes of the HMAC kept in a cursor.  This is plenty to
+/// catch a client editing its cursor, while keeping cursors short.
+pub const CURSOR_MAC_BYTES: usize = 8;
+
+/// The tag mixed into the HMAC of search cursors, so that a token signed
+/// for another purpose with the same key is never accepted as a cursor.
+const CURSOR_PURPOSE: &[u8] = b"cursor\0";
+
+/// The CursorSigner structure produces the search cursors handed to clients,
+/// and checks the ones they send back.  A cursor is the hex encoding of the
+/// offset of the next result, followed by a truncated HMAC of that offset,
+/// so that a client can neither read it easily nor alter it.  WebSocket
+/// resume tokens are signed the same way, under a purpose tag of their own.
+pub struct CursorSigner {
+    key:    Vec<u8>,
+}
+
+impl CursorSigner {
+    pub fn new(key: Vec<u8>) -> CursorSigner {
+        CursorSigner {
+            key,
+        }
+    }
+
+    /// This method returns a signed cursor for the given offset.
+    pub fn encode(&self, offset: u64) -> String {
+        self.sign(CURSOR_PURPOSE, &offset.to_be_bytes())
+    }
+
+    /// This method returns the offset in the given cursor, or None if the
+    /// cursor is malformed or its signature doesn't match.
+    pub fn decode(&self, cursor: &str) -> Option<u64> {
+        let offset = self.verify(CURSOR_PURPOSE, cursor)?;
+
+        Some(u64::from_be_bytes(offset.try_into().ok()?))
+    }
+
+    /// This method returns the hex encoding of the payload followed by a
+    /// truncated HMAC of the purpose tag and the payload.  Tokens only
+    /// verify under the purpose they were signed for.
+    pub fn sign(&self, purpose: &[u8], payload: &[u8]) -> String {
+        let mut token = payload.to_vec();
+        token.extend_from_slice(&self.mac(purpose, payload).finalize().into_bytes()[..CURSOR_MAC_BYTES]);
+
+        hex::encode(token)
+    }
+
+    /// This method returns the payload of a token produced by sign, or
+    /// None if the token is malformed or its signature doesn't match.
+    pub fn verify(&self, purpose: &[u8], token: &str) -> Option<Vec<u8>> {
+        let mut payload = hex::decode(token).ok()?;
+
+        if payload.len() < CURSOR_MAC_BYTES {
+            return None;
+        }
+
+        let signature = payload.split_off(payload.len() - CURSOR_MAC_BYTES);
+        self.mac(purpose, &payload).verify_truncated_left(&signature).ok()?;
+
+        Some(payload)
+    }
+
+    fn mac(&self, purpose: &[u8], payload: &[u8]) -> HmacSha256 {
+        // HMAC accepts keys of any length.
+        let mut mac = HmacSha256::new_from_slice(&self.key).unwrap();
+        mac.update(purpose);
+        mac.update(payload);
+        mac
+    }
+} // end CursorSigner
@@ -0,0 +1,87 @@
+use chrono::{
+    DateTime,
+    Duration,
+    Utc,
+};
+use uuid::Uuid;
+
+use crate::{
+    build_geotag_array,
+    messages::{
+        ChatMessageSchema,
+        GeoLocationType,
+        SenderMetaSchema,
+    },
+    TEST_DOMAIN_ID,
+    timestamp::Timestamp,
+    TEST_ROOM_NAME,
+};
+
+/// The DemoEntry structure is one step of the demo script.
+struct DemoEntry {
+    sender:     &'static str,
+    room_name:  &'static str,
+    text:       &'static str,
+}
+
+/// The script a demo stream cycles through.  Between them, the entries
+/// give every sortable field several distinct values.
+const DEMO_SCRIPT: [DemoEntry; 6] = [
+    DemoEntry { sender: "Alice",   room_name: TEST_ROOM_NAME,          text: "Good morning, the demo stream is starting." },
+    DemoEntry { sender: "Bob",     room_name: "edge-view-demo-room-a", text: "Checking in from room A." },
+    DemoEntry { sender: "Carol",   room_name: "edge-view-demo-room-b", text: "Room B is quiet today." },
+    DemoEntry { sender: "Dave",    room_name: TEST_ROOM_NAME,          text: "Antediluvian is today's keyword." },
+    DemoEntry { sender: "Erin",    room_name: "edge-view-demo-room-a", text: "Room A again, a little later." },
+    DemoEntry { sender: "Frank",   room_name: "edge-view-demo-room-b", text: "That's the end of the cycle." },
+];
+
+/// The DemoGenerator structure produces a room's demo stream, which every
+/// subscriber to the room shares.  The script repeats exactly, apart from
+/// the timestamps, which advance one second per message from when the
+/// room's first subscriber connected.
+pub struct DemoGenerator {
+    index:  u64,
+    start:  DateTime<Utc>,
+}
+
+impl DemoGenerator {
+    pub fn new() -> DemoGenerator {
+        DemoGenerator {
+            index:  0,
+            start:  Utc::now(),
+        }
+    }
+
+    /// This method returns the next message of the script.
+    pub fn next_message(
+        &mut self,
+        classification:     String,
+        geo_location_type:  GeoLocationType,
+        sender_meta:        bool,
+    ) -> ChatMessageSchema {
+        let position = (self.index % DEMO_SCRIPT.len() as u64) as usize;
+        let entry = &DEMO_SCRIPT[position];
+
+        let message = ChatMessageSchema {
+            classification,
+            domain_id:      String::from(TEST_DOMAIN_ID),
+            geo_tags:       Some(build_geotag_array(position as i32, geo_location_type)),
+
+            // Ids repeat with the script, so that every cycle is the same.
+            id:             Uuid::from_u128(position as u128 + 1).to_string(),
+            room_name:      String::from(entry.room_name),
+            sender:         String::from(entry.sender),
+            text:           String::from(entry.text),
+            thread_id:      None,
+            timestamp:      Timestamp::from(self.start + Duration::seconds(self.index as i64)),
+            user_id:        Uuid::from_u128(position as u128 + 1).to_string(),
+            private:        false,
+            sequence:       None,
+            score:          None,
+            sender_meta:    sender_meta.then(|| SenderMetaSchema::for_sender(entry.sender)),
+        };
+
+        self.index += 1;
+        message
+    }
+} // end DemoGenerator
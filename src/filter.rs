@@ -0,0 +1,67 @@
+use crate::messages::ChatMessageSchema;
+
+/// The syntax of a filter expression, reported to clients whose expression
+/// can't be parsed.  It has to fit in a WebSocket close frame's reason.
+pub const FILTER_SYNTAX: &str =
+    "expected <field>=<value> or <field>~<value> with field sender, text, id, userId or threadId";
+
+/// The message fields a filter can test.
+pub const FILTER_FIELDS: [&str; 5] = ["sender", "text", "id", "userId", "threadId"];
+
+/// The MessageFilter structure is a predicate on chat messages, parsed from
+/// an expression such as `sender=Austin` (the field equals the value) or
+/// `text~keyword` (the field contains the value).
+pub struct MessageFilter {
+    field:      String,
+    value:      String,
+    contains:   bool,
+}
+
+impl MessageFilter {
+    /// This method parses a filter expression, returning a description of
+    /// the problem if it isn't supported.
+    pub fn parse(expression: &str) -> Result<MessageFilter, String> {
+        // Split on whichever operator comes first, so that the value may
+        // contain either character.
+        let (split_at, contains) = match (expression.find('='), expression.find('~')) {
+            (Some(equals), Some(tilde)) if tilde < equals => (tilde, true),
+            (Some(equals), _) => (equals, false),
+            (None, Some(tilde)) => (tilde, true),
+            (None, None) => return Err(format!("No operator in filter, {}", FILTER_SYNTAX)),
+        };
+
+        let filter = MessageFilter {
+            field:      String::from(&expression[..split_at]),
+            value:      String::from(&expression[split_at + 1..]),
+            contains,
+        };
+
+        if !FILTER_FIELDS.contains(&filter.field.as_str()) {
+            return Err(format!("Unknown filter field, {}", FILTER_SYNTAX));
+        }
+
+        Ok(filter)
+    }
+
+    /// This method returns true if the message satisfies the filter.
+    pub fn matches(&self, message: &ChatMessageSchema) -> bool {
+        match MessageFilter::field_value(&self.field, message) {
+            Some(field) if self.contains => field.contains(&self.value),
+            Some(field) => field == self.value,
+            None => false,
+        }
+    }
+
+    /// This function returns the named field of the message, using the
+    /// names the fields are given in JSON.
+    fn field_value<'a>(field: &str, message: &'a ChatMessageSchema) -> Option<&'a str> {
+        match field {
+            "sender"    => Some(&message.sender),
+            "text"      => Some(&message.text),
+            "id"        => Some(&message.id),
+            "userId"    => Some(&message.user_id),
+            "threadId"  => Some(message.thread_id.as_deref().unwrap_or("")),
+            _ => None,
+        }
+    }
+} // end MessageFilter
@@ -0,0 +1,54 @@
+use serde::{ Deserialize, Serialize };
+
+/// axum doesn't expose WebSocket continuation frames, so a large message is
+/// instead sent as several complete text frames, each holding one of these
+/// fragments.  A client reassembles the message by concatenating the data of
+/// the fragments sharing an id, in index order.
+#[derive(Serialize, Deserialize)]
+pub struct MessageFragment {
+    // The id of the chat message this fragment belongs to.
+    pub id:     String,
+    pub index:  usize,
+    pub count:  usize,
+    pub data:   String,
+}
+
+/// This function splits the given text into fragments of at most the given
+/// number of bytes.  Fragments only end on character boundaries, so one
+/// may run over the limit when a single character is longer than it.
+pub fn split_message(
+    id:             &str,
+    text:           &str,
+    fragment_bytes: usize,
+) -> Vec<MessageFragment> {
+    let mut chunks: Vec<&str> = Vec::new();
+    let mut start = 0;
+
+    while start < text.len() {
+        let mut end = (start + fragment_bytes).min(text.len());
+
+        while !text.is_char_boundary(end) {
+            end -= 1;
+        }
+
+        // Always make progress, even if that means overrunning the limit.
+        if end == start {
+            end = start + text[start..].chars().next().unwrap().len_utf8();
+        }
+
+        chunks.push(&text[start..end]);
+        start = end;
+    }
+
+    let count = chunks.len();
+
+    chunks.into_iter()
+        .enumerate()
+        .map(|(index, data)| MessageFragment {
+            id:     String::from(id),
+            index,
+            count,
+            data:   String::from(data),
+        })
+        .collect()
+} // end split_message
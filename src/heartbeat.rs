@@ -0,0 +1,29 @@
+use serde::{ Deserialize, Serialize };
+
+/// The HeartbeatFrame structure is interleaved with the chat messages on a
+/// WebSocket stream when the client asks for heartbeats, so that it can
+/// tell a live but quiet server from a dead one.
+///
+/// `{"type":"heartbeat","ts":"2024-01-01T00:00:00+00:00","seq":0}`
+#[derive(Serialize, Deserialize)]
+pub struct HeartbeatFrame {
+    // This field is always "heartbeat", distinguishing these frames from
+    // chat messages.
+    pub r#type: String,
+
+    // The RFC 3339 time at which the frame was sent.
+    pub ts:     String,
+
+    // The heartbeats sent on a connection are numbered from zero.
+    pub seq:    u64,
+}
+
+impl HeartbeatFrame {
+    pub fn new(ts: String, seq: u64) -> HeartbeatFrame {
+        HeartbeatFrame {
+            r#type: String::from("heartbeat"),
+            ts,
+            seq,
+        }
+    }
+} // end HeartbeatFrame
@@ -0,0 +1,86 @@
+use serde::{ Deserialize, Serialize };
+use std::time::Duration;
+use tokio::time::Instant;
+
+/// The period over which inbound frames are counted.
+const INBOUND_RATE_WINDOW: Duration = Duration::from_secs(1);
+
+/// The ErrorFrame structure tells a WebSocket client that the server
+/// refused something it sent.
+///
+/// `{"type":"error","message":"..."}`
+#[derive(Serialize, Deserialize)]
+pub struct ErrorFrame {
+    // This field is always "error", distinguishing these frames from chat
+    // messages.
+    pub r#type:     String,
+
+    pub message:    String,
+}
+
+impl ErrorFrame {
+    pub fn new(message: String) -> ErrorFrame {
+        ErrorFrame {
+            r#type: String::from("error"),
+            message,
+        }
+    }
+} // end ErrorFrame
+
+/// This enum says what to do with a frame received from a client.
+#[derive(Debug, PartialEq)]
+pub enum InboundVerdict {
+    // The frame is within the client's rate.
+    Accept,
+
+    // The frame is the first over the rate this second, so it is dropped
+    // and the client warned.
+    Warn,
+
+    // The frame is over the rate, and the client has been warned already.
+    Drop,
+
+    // The client has sent twice its rate this second, so the connection
+    // is closed.
+    Close,
+}
+
+/// The InboundLimiter structure counts the frames a client sends each
+/// second, so that a client flooding the server can be told to slow down,
+/// and cut off if it doesn't.
+pub struct InboundLimiter {
+    rate:           u32,
+    window_start:   Instant,
+    count:          u32,
+}
+
+impl InboundLimiter {
+    pub fn new(rate: u32) -> InboundLimiter {
+        InboundLimiter {
+            rate,
+            window_start:   Instant::now(),
+            count:          0,
+        }
+    }
+
+    /// This method counts a frame received now, returning what to do
+    /// with it.
+    pub fn check(&mut self) -> InboundVerdict {
+        if self.window_start.elapsed() >= INBOUND_RATE_WINDOW {
+            self.window_start = Instant::now();
+            self.count = 0;
+        }
+
+        self.count += 1;
+
+        if self.count <= self.rate {
+            InboundVerdict::Accept
+        } else if self.count == self.rate + 1 {
+            InboundVerdict::Warn
+        } else if self.count <= self.rate * 2 {
+            InboundVerdict::Drop
+        } else {
+            InboundVerdict::Close
+        }
+    }
+} // end InboundLimiter
@@ -0,0 +1,99 @@
+use serde::Serialize;
+use std::{
+    collections::VecDeque,
+    fmt,
+    sync::{
+        Arc,
+        Mutex,
+    },
+};
+use tracing::{
+    field::{
+        Field,
+        Visit,
+    },
+    Event,
+    Subscriber,
+};
+use tracing_subscriber::{
+    layer::Context,
+    Layer,
+};
+
+use crate::timestamp::Timestamp;
+
+/// The number of log events kept for the logs route.
+pub const LOG_BUFFER_CAPACITY: usize = 1000;
+
+/// The LogRecord structure is one log event, as returned by the logs route.
+#[derive(Clone, Serialize)]
+pub struct LogRecord {
+    pub timestamp:  Timestamp,
+    pub level:      String,
+    pub target:     String,
+    pub message:    String,
+}
+
+/// The LogBuffer structure is a tracing layer keeping the most recent log
+/// events in memory, so that they can be fetched over HTTP when the
+/// server's output isn't to hand.
+#[derive(Clone, Default)]
+pub struct LogBuffer {
+    records:    Arc<Mutex<VecDeque<LogRecord>>>,
+}
+
+impl LogBuffer {
+    /// This method returns up to the given number of the most recent
+    /// events, oldest first.
+    pub fn recent(&self, count: usize) -> Vec<LogRecord> {
+        let records = self.records.lock().unwrap();
+
+        records.iter()
+            .skip(records.len().saturating_sub(count))
+            .cloned()
+            .collect()
+    }
+} // end LogBuffer
+
+impl<S: Subscriber> Layer<S> for LogBuffer {
+    fn on_event(&self, event: &Event<'_>, _context: Context<'_, S>) {
+        let mut visitor = MessageVisitor::default();
+        event.record(&mut visitor);
+
+        let record = LogRecord {
+            timestamp:  Timestamp::now(),
+            level:      event.metadata().level().to_string(),
+            target:     String::from(event.metadata().target()),
+            message:    std::iter::once(visitor.message)
+                .chain(visitor.fields)
+                .filter(|part| !part.is_empty())
+                .collect::<Vec<String>>()
+                .join(" "),
+        };
+
+        let mut records = self.records.lock().unwrap();
+
+        if records.len() == LOG_BUFFER_CAPACITY {
+            records.pop_front();
+        }
+        records.push_back(record);
+    }
+}
+
+/// The MessageVisitor structure formats an event's fields as the fmt
+/// layer does: the message, followed by any other fields as name=value.
+#[derive(Default)]
+struct MessageVisitor {
+    message:    String,
+    fields:     Vec<String>,
+}
+
+impl Visit for MessageVisitor {
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        if field.name() == "message" {
+            self.message = format!("{:?}", value);
+        } else {
+            self.fields.push(format!("{}={:?}", field.name(), value));
+        }
+    }
+}
@@ -1,40 +1,126 @@
+mod capture;
+mod cursor;
+mod demo;
+mod filter;
+mod fragment;
+mod heartbeat;
+mod inbound;
+mod logs;
 mod messages;
+mod middleware;
+mod places;
+mod projection;
+mod resume;
+mod rooms;
+mod self_test;
+mod server;
+mod templates;
+mod timestamp;
+mod token;
+mod unicode;
+use anyhow::Context;
 use axum::{
-    extract::ws::{
-        Message,
-        WebSocketUpgrade,
-        WebSocket,
+    extract::{
+        Path,
+        Query,
+        State,
+        ws::{
+            close_code,
+            CloseFrame,
+            Message,
+            WebSocketUpgrade,
+        },
+    },
+    http::header::{
+        ACCEPT,
+        CONTENT_TYPE,
+        ETAG,
+        HeaderMap,
+        HeaderName,
+        HeaderValue,
+        IF_NONE_MATCH,
+    },
+    response::{
+        Html,
+        IntoResponse,
+        Response,
     },
-    http::header::HeaderMap,
-    response::Json as response_json,
-    response::Response,
     Router,
-    routing::get,
-    routing::post,
+    routing::{
+        MethodFilter,
+        MethodRouter,
+        on,
+    },
 };
 use chrono::Utc;
 use clap::Parser;
-use hyper::StatusCode;
+use futures_util::StreamExt;
+use hyper::{
+    Method,
+    StatusCode,
+};
 use messages::{
     ChatMessageSchema,
+    Classification,
+    GeoLocationType,
     GetApiResponse,
-    GetChatMessagesResponse,
     RegionSchema,
     TimeFilterResponse
 };
-use rand::Rng;
+use rand::{
+    distributions::{
+        uniform::{
+            SampleRange,
+            SampleUniform,
+        },
+        Distribution,
+        Standard,
+    },
+    rngs::StdRng,
+    Rng,
+    SeedableRng,
+};
+use serde::{
+    Deserialize,
+    Serialize,
+};
+use sha2::{
+    Digest,
+    Sha256,
+};
 use std::{
-    thread,
+    collections::{
+        hash_map::Entry,
+        HashMap,
+    },
+    num::NonZeroUsize,
+    str::FromStr,
+    sync::{
+        atomic::{
+            AtomicU64,
+            Ordering,
+        },
+        Arc,
+        Mutex,
+        OnceLock,
+    },
     time::{
         Duration,
-        self,
+        Instant,
     }
 };
-use thread_id;
+use strum::IntoEnumIterator;
+use timestamp::Timestamp;
+use tokio::sync::broadcast;
 use tracing::{event, Level};
-use tracing_subscriber;
+use tracing_subscriber::{
+    layer::SubscriberExt,
+    util::SubscriberInitExt,
+};
 use uuid::Uuid;
 
+pub const LOG_LEVEL: Level = Level::DEBUG;
+
 pub const WS_UNCLASSIFIED_URL: &str = "wss://localhost/root";
 pub const DEFAULT_SERVE_IP: &str = "0.0.0.0";
 pub const DEFAULT_SERVE_PORT: i32 = 80;
@@ -44,17 +130,134 @@ pub const TEST_ROOM_NAME: &str = "edge-view-test-room";
 pub const TEST_DOMAIN_ID: &str = "chatsurferxmppunclass"; 
 pub const TEST_KEYWORD: &str = "Antediluvian";
 
+// The API key handed out by the get API key route, which WebSocket
+// clients must present when --require_ws_auth is set.
+pub const TEST_API_KEY: &str = "a7B5siy9xY1dmN";
+
+pub const PUBLIC_KEY_ROUTE: &str = "/auth/realms/fmv";
+pub const TOKEN_ROUTE: &str = "/keycloak/token";
 pub const GET_API_KEY_ROUTE: &str = "/api/auth/key";
 pub const MESSAGES_ROUTE: &str = "/api/chat/messages/chatsurferxmppunclass/edge-view-test-room";
 pub const NEW_MESSAGE_ROUTE: &str = "/api/chatserver/message";
+pub const BULK_MESSAGES_ROUTE: &str = "/api/chatserver/messages/bulk";
 pub const SEARCH_MESSAGES_ROUTE: &str = "/api/chat/messages/search";
+pub const ROOM_CLASSIFICATION_ROUTE: &str = "/api/chat/classification/:domain_id/:room_name";
+pub const CLASSIFICATIONS_ROUTE: &str = "/api/chat/classifications";
 
 pub const WS_SINGLE_ROOM_ROUTE: &str = "/topic/chat-messages-room/chatsurferxmppunclass/edge-view-test-room";
 
-pub const SECONDS_BETWEEN_WEBSOCKET_UPDATE: u64 = 1;
+pub const TEST_ROUTE: &str = "/test";
+pub const LIST_ROUTES_ROUTE: &str = "/api/test/routes";
+pub const SERVER_STATE_ROUTE: &str = "/api/test/state";
+pub const ECHO_ROUTE: &str = "/api/test/echo";
+pub const WS_INTERVAL_ROUTE: &str = "/api/test/ws_interval";
+pub const BUSY_ROUTE: &str = "/api/test/busy";
+pub const INJECT_ROUTE: &str = "/api/test/inject";
+pub const SLOW_BODY_ROUTE: &str = "/api/test/slow_body";
+pub const OPENAPI_ROUTE: &str = "/openapi.json";
+pub const DOCS_ROUTE: &str = "/docs";
+pub const LOGS_ROUTE: &str = "/api/test/logs";
+
+// The routes a load balancer or monitor checks the server's health with.
+pub const HEALTH_ROUTES: [&str; 2] = [TEST_ROUTE, SERVER_STATE_ROUTE];
+
+/// This struct describes one of the routes served by the mock server.
+pub struct RouteDescription {
+    pub method:     Method,
+    pub path:       &'static str,
+
+    // Data routes are the ones that mimic the ChatSurfer HTTP API.
+    pub data:       bool,
+
+    // A one line description of the route, for the API documentation.
+    pub summary:    &'static str,
+}
+
+/// This list is the single source of truth for the routes we serve.  The
+/// router is built from it, and the list routes and OpenAPI handlers
+/// report it.
+pub const ROUTES: &[RouteDescription] = &[
+    RouteDescription { method: Method::GET,  path: PUBLIC_KEY_ROUTE,      data: false, summary: "Get the realm's public key" },
+    RouteDescription { method: Method::POST, path: TOKEN_ROUTE,           data: false, summary: "Issue an access token signed with the realm's key" },
+    RouteDescription { method: Method::GET,  path: GET_API_KEY_ROUTE,     data: true,  summary: "Get the user's API key" },
+    RouteDescription { method: Method::GET,  path: MESSAGES_ROUTE,        data: true,  summary: "Get the test room's messages" },
+    RouteDescription { method: Method::POST, path: NEW_MESSAGE_ROUTE,     data: true,  summary: "Post a message to a room" },
+    RouteDescription { method: Method::POST, path: BULK_MESSAGES_ROUTE,   data: true,  summary: "Post several messages at once" },
+    RouteDescription { method: Method::POST, path: SEARCH_MESSAGES_ROUTE, data: true,  summary: "Search the messages by keyword" },
+    RouteDescription { method: Method::GET,  path: ROOM_CLASSIFICATION_ROUTE, data: true, summary: "Get the highest classification of a room's messages" },
+    RouteDescription { method: Method::GET,  path: CLASSIFICATIONS_ROUTE, data: true,  summary: "List the classifications from lowest to highest" },
+    RouteDescription { method: Method::GET,  path: WS_SINGLE_ROOM_ROUTE,  data: false, summary: "Stream the test room's messages over a WebSocket" },
+    RouteDescription { method: Method::GET,  path: TEST_ROUTE,            data: false, summary: "Report the server's configuration" },
+    RouteDescription { method: Method::GET,  path: LIST_ROUTES_ROUTE,     data: false, summary: "List the routes served" },
+    RouteDescription { method: Method::GET,  path: SERVER_STATE_ROUTE,    data: false, summary: "Report the generation state" },
+    RouteDescription { method: Method::POST, path: ECHO_ROUTE,            data: false, summary: "Echo the request body" },
+    RouteDescription { method: Method::POST, path: WS_INTERVAL_ROUTE,     data: false, summary: "Change the WebSocket push interval" },
+    RouteDescription { method: Method::GET,  path: BUSY_ROUTE,            data: false, summary: "Spin the CPU for a number of milliseconds" },
+    RouteDescription { method: Method::POST, path: INJECT_ROUTE,          data: false, summary: "Store a complete chat message" },
+    RouteDescription { method: Method::GET,  path: SLOW_BODY_ROUTE,       data: false, summary: "Get the test room's messages, slowly" },
+    RouteDescription { method: Method::GET,  path: OPENAPI_ROUTE,         data: false, summary: "Get the OpenAPI description of the server" },
+    RouteDescription { method: Method::GET,  path: DOCS_ROUTE,            data: false, summary: "Browse the API documentation" },
+    RouteDescription { method: Method::GET,  path: LOGS_ROUTE,            data: false, summary: "Get the most recent log events" },
+];
+
+pub const DEFAULT_WS_INTERVAL_MS: u64 = 1000;
+
+// The longest the busy route will spin the CPU for, however long it is
+// asked to.
+pub const MAX_BUSY_MS: u64 = 10_000;
+pub const SECONDS_BETWEEN_LIVENESS_LOGS: u64 = 10;
+pub const SECONDS_BETWEEN_RESUME_TOKENS: u64 = 5;
+
+// Clients told to retry a warming up route are asked to wait this long,
+// plus up to the jitter, so that their retries spread out.
+pub const WARMUP_RETRY_AFTER_SECS: u64 = 1;
+pub const WARMUP_RETRY_AFTER_JITTER_SECS: u64 = 2;
 
 pub const MAX_REGIONS: usize = 5;
 
+pub const DEFAULT_HEARTBEAT_INTERVAL_SECS: u64 = 5;
+
+// An occurrence of a search keyword in the same case counts this many
+// times towards a message's relevance score.
+pub const EXACT_CASE_WEIGHT: u32 = 2;
+
+pub const DEFAULT_HIGHLIGHT_PREFIX: &str = "<em>";
+pub const DEFAULT_HIGHLIGHT_SUFFIX: &str = "</em>";
+
+pub const TRUNCATED_HEADER: &str = "x-truncated";
+pub const XML_CONTENT_TYPE: &str = "application/xml";
+
+// The defaults for the slow body route: how long to wait between chunks,
+// and how big they are.
+pub const DEFAULT_SLOW_BODY_CHUNK_DELAY_MS: u64 = 50;
+pub const DEFAULT_SLOW_BODY_CHUNK_BYTES: usize = 256;
+
+// The claims of the tokens issued by the token route, unless the request
+// gives others.
+pub const DEFAULT_TOKEN_SUBJECT: &str = "Austin";
+pub const DEFAULT_TOKEN_REALM: &str = "fmv";
+pub const DEFAULT_TOKEN_EXPIRES_IN_SECS: u64 = 300;
+
+// The number of log events returned by the logs route by default.
+pub const DEFAULT_LOGS_COUNT: usize = 50;
+
+// The number of bytes of a body's SHA-256 hash used in its ETag.
+pub const ETAG_HASH_BYTES: usize = 16;
+
+// The malformed classification markings a generated message may be
+// given, for testing how clients fall back.
+pub const INVALID_CLASSIFICATIONS: [&str; 4] = ["UNCLAS", "", "SECRET//BOGUS", "unclassified"];
+
+pub const DEFAULT_MAX_MESSAGE_LEN: usize = 4096;
+pub const REJECTED_MESSAGE_PREVIEW_CHARS: usize = 64;
+
+pub const IDEMPOTENCY_KEY_HEADER: &str = "idempotency-key";
+pub const DEFAULT_IDEMPOTENCY_TTL_SECS: u64 = 300;
+
+// The number of posted messages a slow WebSocket subscriber can fall
+// behind by before it starts missing them.
+pub const BROADCAST_CAPACITY: usize = 100;
+
 fn build_region_array(
     seed:   i32,
     length: usize
@@ -62,101 +265,313 @@ fn build_region_array(
     let mut temp_vector: Vec<messages::RegionSchema> = Vec::new();
     let mut index: usize = 0;
 
+    // Walk the table of places starting from the seed's place, so the
+    // first region always matches the geo-tag's location.
     while index < length {
-        temp_vector.insert(index, RegionSchema::new_test(seed as f32));
+        let place = places::place_for_seed(seed.wrapping_add(index as i32));
+
+        temp_vector.insert(index, RegionSchema {
+            abbreviation:   String::from(place.abbreviation),
+            bounds:         place.bounds(),
+            description:    format!("{}, {}", place.name, place.country),
+            name:           String::from(place.name),
+            region_type:    String::from("City"),
+        });
         index += 1;
     }
 
     temp_vector
 }
 
-fn build_geotag(seed: i32) -> messages::GeoTagSchema {
+fn build_geotag(seed: i32, location_type: GeoLocationType) -> messages::GeoTagSchema {
+    let place = places::place_for_seed(seed);
+
+    let location = match location_type.for_seed(seed) {
+        messages::LocationType::Point => messages::LocationSchema::from_point(place.point()),
+        messages::LocationType::Polygon => messages::LocationSchema::from_polygon(place.polygon()),
+    };
+
     messages::GeoTagSchema {
         anchor_end:      seed as i64,
         anchor_start:    seed as i64,
-        anchor_text:     String::from(format!("Anchor text for GeoTag {}", seed)),
+        anchor_text:     String::from(place.name),
         confidence:     seed as f32,
-        location:       messages::LocationSchema::init(
-                            1.0,
-                            messages::LocationType::Point),
+        location,
         regions:        build_region_array(
                             seed,
                             MAX_REGIONS),
-        r#type: String::from(format!("PAL"))
+        r#type: "PAL".to_string()
     }
 }
 
-fn build_geotag_array(seed: i32) -> Vec<messages::GeoTagSchema> {
-    vec!(build_geotag(seed))
+fn build_geotag_array(seed: i32, location_type: GeoLocationType) -> Vec<messages::GeoTagSchema> {
+    vec!(build_geotag(seed, location_type))
 }
 
 fn build_chat_message(
+    state: &ServerState,
     seed: i32,
     new_name: &str,
     additional_text: &str,
 ) -> messages::ChatMessageSchema {
 
+    state.messages_generated.fetch_add(1, Ordering::Relaxed);
+
+    // Occasionally leave out the optional fields so that clients have to
+    // handle their absence.
+    let sparse = state.random_bool(state.args.sparse_fields);
+
+    // Shift the timestamp by up to the configured skew in either
+    // direction, so that messages can appear out of order.
+    let mut timestamp = Utc::now();
+
+    if state.args.clock_skew_secs > 0 {
+        let skew = state.args.clock_skew_secs as i64;
+        timestamp += chrono::Duration::seconds(state.random_range(-skew..=skew));
+    }
+
+    // Take the content from the template file if there is one, or else
+    // swap in multibyte content for clients testing their text handling.
+    // The additional text is kept, so that searches still match.
+    let templates = state.templates.as_ref().map(|store| store.templates());
+
+    let (sender, base_text) = if let Some(templates) = &templates {
+        let template = &templates[state.random_range(0..templates.len())];
+        (template.sender.as_str(), template.text.as_str())
+    } else if state.args.unicode_sample {
+        (
+            unicode::UNICODE_SENDERS[state.random_range(0..unicode::UNICODE_SENDERS.len())],
+            unicode::UNICODE_TEXTS[state.random_range(0..unicode::UNICODE_TEXTS.len())],
+        )
+    } else {
+        (new_name, "This is some test message text.")
+    };
+
+    // Draw each message's classification from the mix, if one is given.
+    let mut classification = match state.args.classification_mix.len() {
+        0 => state.args.classification,
+        count => state.args.classification_mix[state.random_range(0..count)],
+    }.to_string();
+
+    // Occasionally replace it with a malformed marking.  Skipping the draw
+    // when disabled keeps seeded output as it was.
+    if state.args.invalid_classification_rate > 0.0
+        && state.random_bool(state.args.invalid_classification_rate) {
+        let index = state.random_range(0..INVALID_CLASSIFICATIONS.len());
+        classification = String::from(INVALID_CLASSIFICATIONS[index]);
+    }
+
     messages::ChatMessageSchema {
-        classification: String::from(UNCLASSIFIED_STRING),
+        classification,
         domain_id:      String::from(TEST_DOMAIN_ID),
-        geo_tags:       Some(build_geotag_array(seed)),
+        geo_tags:       if sparse { None } else { Some(build_geotag_array(seed, state.args.geo_location_type)) },
         id:             Uuid::new_v4().to_string(),
         room_name:      String::from(TEST_ROOM_NAME),
-        sender:         String::from(new_name),
-        text:           String::from(format!("{}{}", 
-            "This is some test message text.",
-            additional_text)),
-        thread_id:      Some(Uuid::new_v4().to_string()),
-        timestamp:      Utc::now().to_string(),
+        sender:         String::from(sender),
+        text:           format!("{}{}", base_text, additional_text),
+        thread_id:      if sparse { None } else { Some(Uuid::new_v4().to_string()) },
+        timestamp:      Timestamp::from(timestamp),
         user_id:        Uuid::new_v4().to_string(),
         private:        false,
+        sequence:       None,
+        score:          None,
+        sender_meta:    state.args.sender_meta.then(|| messages::SenderMetaSchema::for_sender(sender)),
     }
 } //end build_chat_message
 
-fn build_get_messages_response() -> messages::GetChatMessagesResponse {
-    let mut messages = Vec::new();
-
-    messages.push(build_chat_message(
-        25,
-        "Austin",
-        TEST_KEYWORD
-    ));
-    messages.push(build_chat_message(4, "Tyler", ""));
-    messages.push(build_chat_message(7, "Joe", TEST_KEYWORD));
-    messages.push(build_chat_message(9, "Jeremy", ""));
-    messages.push(build_chat_message(2, "Trevor", ""));
-    messages.push(build_chat_message(4, "Justin", TEST_KEYWORD));
-    messages.push(build_chat_message(97856, "Ryan", ""));
-    messages.push(build_chat_message(123, "Joseph", ""));
-    messages.push(build_chat_message(432, "Rita", ""));
-    messages.push(build_chat_message(654, "Matt", ""));
+/// This function generates the fixed set of messages every room starts
+/// with.
+fn build_seeded_messages(state: &ServerState) -> Vec<ChatMessageSchema> {
+    let messages = vec!(
+        build_chat_message(state, 25, "Austin", TEST_KEYWORD),
+        build_chat_message(state, 4, "Tyler", ""),
+        build_chat_message(state, 7, "Joe", TEST_KEYWORD),
+        build_chat_message(state, 9, "Jeremy", ""),
+        build_chat_message(state, 2, "Trevor", ""),
+        build_chat_message(state, 4, "Justin", TEST_KEYWORD),
+        build_chat_message(state, 97856, "Ryan", ""),
+        build_chat_message(state, 123, "Joseph", ""),
+        build_chat_message(state, 432, "Rita", ""),
+        build_chat_message(state, 654, "Matt", ""),
+    );
+
+    messages
+}
+
+/// This function returns the seeded messages followed by the messages
+/// posted to any room.
+fn all_messages(state: &ServerState) -> Vec<ChatMessageSchema> {
+    let mut messages = state.seeded_messages().to_vec();
+    messages.extend(state.stored_messages());
+    messages
+}
 
+fn build_get_messages_response(state: &ServerState) -> messages::GetChatMessagesResponse {
+    let mut messages = all_messages(state);
+    messages.retain(|message|
+        message.domain_id == TEST_DOMAIN_ID && message.room_name == TEST_ROOM_NAME);
 
     messages::GetChatMessagesResponse {
-        classification: messages::UNCLASSIFIED_STRING.to_string(),
-        messages: messages,
+        classification: state.args.classification.to_string(),
+        messages,
         domain_id: String::from(TEST_DOMAIN_ID),
         room_name: String::from(TEST_ROOM_NAME),
         private: false,
+        total: None,
     }
 }
 
-fn search_messages(keywords: String) -> Vec<ChatMessageSchema> {
-    let mut search_results: Vec<ChatMessageSchema> = Vec::new();
+/// This function splits a search query into its keywords, leaving out
+/// any repeats so that a keyword isn't counted or scored twice.
+fn split_query(query: &str) -> Vec<&str> {
+    let mut keywords: Vec<&str> = Vec::new();
 
-    let mut split_keywords: Vec<&str> = keywords.split(" ").collect();
-    split_keywords.retain(|&x| x != "");
+    for keyword in query.split(' ').filter(|keyword| !keyword.is_empty()) {
+        if !keywords.contains(&keyword) {
+            keywords.push(keyword);
+        }
+    }
+
+    keywords
+}
+
+/// This function returns the messages matching the given keywords, each
+/// with its relevance score, along with the number of messages containing
+/// each individual keyword.  When a room or time filter is given, only
+/// messages passing it are searched, so that the counts agree with the
+/// results.
+fn search_messages(
+    state:          &ServerState,
+    keywords:       String,
+    room_filter:    Option<&messages::DomainFilterDetail>,
+    time_filter:    Option<&messages::TimeFilterRequest>,
+) -> (Vec<(u32, ChatMessageSchema)>, HashMap<String, i32>) {
+    let mut search_results: Vec<(u32, ChatMessageSchema)> = Vec::new();
+    let mut keyword_counts: HashMap<String, i32> = HashMap::new();
+
+    let split_keywords = split_query(&keywords);
     event!(Level::DEBUG, "{:?}", split_keywords);
 
-    let messages = build_get_messages_response().messages;
+    // Report keywords without any matches too.
+    for keyword in &split_keywords {
+        keyword_counts.insert(keyword.to_string(), 0);
+    }
+
+    let mut messages = all_messages(state);
+
+    if let Some(filter) = room_filter {
+        messages.retain(|message| filter.contains(&message.domain_id, &message.room_name));
+    }
+
+    if let Some(filter) = time_filter {
+        messages.retain(|message| filter.contains(&message.timestamp));
+    }
 
     for message in messages {
-        if message.text.contains(split_keywords.first().unwrap()) {
-            search_results.push(message);
+        for keyword in &split_keywords {
+            if message.text.contains(keyword) {
+                *keyword_counts.get_mut(*keyword).unwrap() += 1;
+            }
+        }
+
+        // A query without any keywords matches every message.
+        if split_keywords.first().is_none_or(|first| message.text.contains(first)) {
+            search_results.push((relevance_score(&message.text, &split_keywords), message));
+        }
+    }
+
+    (search_results, keyword_counts)
+}
+
+/// This function scores the relevance of a message's text to the given
+/// keywords, by counting the occurrences of each.  Occurrences matching
+/// the keyword's case count for more than those that only match when
+/// case is ignored.
+fn relevance_score(text: &str, keywords: &[&str]) -> u32 {
+    let lowercase_text = text.to_lowercase();
+
+    keywords.iter()
+        .map(|keyword| {
+            let exact = text.matches(keyword).count() as u32;
+            let ignoring_case = lowercase_text.matches(&keyword.to_lowercase()).count() as u32;
+
+            exact * EXACT_CASE_WEIGHT + ignoring_case.saturating_sub(exact)
+        })
+        .sum()
+} // end relevance_score
+
+/// This function wraps every occurrence of the given keywords in the text
+/// with the prefix and suffix.  Where keywords overlap, the one starting
+/// first wins, so the markup itself is never highlighted.
+fn highlight_keywords(
+    text:       &str,
+    keywords:   &[&str],
+    prefix:     &str,
+    suffix:     &str,
+) -> String {
+    let mut highlighted = String::new();
+    let mut remaining = text;
+
+    loop {
+        // Find the earliest match, preferring the longest keyword there.
+        let next_match = keywords.iter()
+            .filter_map(|keyword| remaining.find(keyword).map(|start| (start, keyword.len())))
+            .min_by_key(|&(start, length)| (start, std::cmp::Reverse(length)));
+
+        match next_match {
+            Some((start, length)) => {
+                highlighted.push_str(&remaining[..start]);
+                highlighted.push_str(prefix);
+                highlighted.push_str(&remaining[start..start + length]);
+                highlighted.push_str(suffix);
+                remaining = &remaining[start + length..];
+            }
+            None => {
+                highlighted.push_str(remaining);
+                return highlighted;
+            }
+        }
+    }
+} // end highlight_keywords
+
+/// This function serializes a response body in the format the client asked
+/// for in its Accept header, which is XML for application/xml and JSON
+/// otherwise.  The XML document's root element takes the given name.
+fn negotiate_body<T: Serialize>(
+    request_headers:    &HeaderMap,
+    root:               &str,
+    body:               &T,
+) -> (HeaderMap, String) {
+    let mut response_headers = HeaderMap::new();
+
+    let wants_xml = request_headers.get(ACCEPT)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|accept| accept.contains(XML_CONTENT_TYPE));
+
+    if wants_xml {
+        // quick-xml can't serialize some of our enums directly, so go by
+        // way of their JSON representation.
+        let value = serde_json::to_value(body).unwrap();
+
+        match quick_xml::se::to_string_with_root(root, &value) {
+            Ok(xml) => {
+                response_headers.insert(CONTENT_TYPE, HeaderValue::from_static(XML_CONTENT_TYPE));
+                return (response_headers, xml);
+            }
+            Err(e) => {
+                event!(Level::ERROR, "Error - could not serialize the response as XML, sending JSON: {}", e);
+            }
         }
     }
 
-    search_results
+    (response_headers, serde_json::to_string(body).unwrap())
+} // end negotiate_body
+
+/// This function logs the given error body and packages it up as a
+/// 400 Bad Request response.
+fn bad_request(body: messages::ErrorCode400) -> (StatusCode, String) {
+    event!(Level::DEBUG, "{}", body);
+    (StatusCode::BAD_REQUEST, body.to_string())
 }
 
 async fn handle_get_api_key() -> (StatusCode, String) {
@@ -168,16 +583,32 @@ async fn handle_get_api_key() -> (StatusCode, String) {
         classification: String::from(UNCLASSIFIED_STRING),
         dn:             String::from("CN=Austin,O=Nine Hill Technology,ST=New York,C=US"),
         email:          String::from("austin.farrell@ninehilltech.com"),
-        key:            String::from("a7B5siy9xY1dmN"),
+        key:            String::from(TEST_API_KEY),
         status:         serde_json::to_string(&messages::ApiKeyStatus::ACTIVE).unwrap(),
     };
 
     (StatusCode::OK, serde_json::to_string(&response).unwrap())
 } // end handle_get_api_key
 
+/// This struct describes the query parameters accepted by the get
+/// messages route, which select a page of the room's messages.
+#[derive(Deserialize)]
+struct GetMessagesQuery {
+    count:  Option<usize>,
+
+    #[serde(default)]
+    offset: usize,
+
+    // A comma separated list of the geo-tag fields to return, leaving
+    // out the rest.
+    geo_fields: Option<String>,
+}
+
 async fn handle_get_messages(
+    State(state): State<ServerState>,
+    Query(query): Query<GetMessagesQuery>,
     headers:    HeaderMap,
-) -> (StatusCode, String) {
+) -> (StatusCode, HeaderMap, String) {
     event!(Level::DEBUG, "Received the Get Messages Request");
 
     if headers.contains_key("api-key") {
@@ -185,15 +616,152 @@ async fn handle_get_messages(
         event!(Level::DEBUG, "{}", key_value.to_str().unwrap())
     }
 
-    let response: messages::GetChatMessagesResponse;
-    response = build_get_messages_response();
+    
+    let mut response: messages::GetChatMessagesResponse = build_get_messages_response(&state);
+    let mut response_headers = HeaderMap::new();
+    let available = response.messages.len();
+
+    // An offset past the end of the messages leaves an empty page.
+    response.messages.drain(..query.offset.min(available));
+
+    if let Some(count) = query.count {
+        response.messages.truncate(count);
+    }
+
+    // Bound the number of messages returned, telling the client how many
+    // there were in total when some have been left out.
+    if let Some(max) = state.args.max_get_messages {
+        if response.messages.len() > max {
+            event!(Level::DEBUG, "Truncating {} messages to {}", response.messages.len(), max);
+
+            response.total = Some(available);
+            response.messages.truncate(max);
+            response_headers.insert(TRUNCATED_HEADER, HeaderValue::from_static("true"));
+        }
+    }
+
+    // The response as a whole is marked with the highest classification
+    // of the messages it holds.
+    if let Some(highest) = highest_classification(&response.messages) {
+        response.classification = highest.to_string();
+    }
 
     event!(Level::DEBUG, "Sending the response");
 
-    (StatusCode::OK, serde_json::to_string(&response).unwrap())
+    let (content_headers, body) = match query.geo_fields.as_deref() {
+        Some(selection) => negotiate_body(&headers, "GetChatMessagesResponse",
+            &projection::project_geo_tags(&response, selection)),
+        None => negotiate_body(&headers, "GetChatMessagesResponse", &response),
+    };
+    response_headers.extend(content_headers);
+
+    // The ETag has to describe the bytes actually sent, so the body is put
+    // in the canonical form the middleware would give it before hashing.
+    let body = if state.args.canonical_json {
+        middleware::canonicalize_json(body.as_bytes()).unwrap_or(body)
+    } else {
+        body
+    };
+
+    // Let clients revalidate what they have cached, answering with an
+    // empty 304 when the body hasn't changed.
+    let etag = body_etag(&body);
+    response_headers.insert(ETAG, HeaderValue::from_str(&etag).unwrap());
+
+    if etag_matches(&headers, &etag) {
+        event!(Level::DEBUG, "The client's copy is current, sending 304 Not Modified");
+        return (StatusCode::NOT_MODIFIED, response_headers, String::new());
+    }
+
+    (StatusCode::OK, response_headers, body)
+}
+
+/// This function returns the highest classification among the given
+/// messages, ignoring any it doesn't recognize.
+fn highest_classification(messages: &[ChatMessageSchema]) -> Option<Classification> {
+    messages.iter()
+        .filter_map(|message| Classification::from_str(&message.classification).ok())
+        .max()
+}
+
+/// This function reports the highest classification among the stored
+/// messages of a room, so that clients can check the banner they compute
+/// for it.  A room with no messages reports the lowest classification.
+async fn handle_room_classification(
+    State(state): State<ServerState>,
+    Path((domain_id, room_name)): Path<(String, String)>,
+) -> (StatusCode, String) {
+    event!(Level::DEBUG, "Received the Room Classification Request for {}/{}", domain_id, room_name);
+
+    let mut messages = all_messages(&state);
+    messages.retain(|message| message.domain_id == domain_id && message.room_name == room_name);
+
+    let body = serde_json::json!({
+        "classification": highest_classification(&messages).unwrap_or_default(),
+    });
+
+    (StatusCode::OK, body.to_string())
+} // end handle_room_classification
+
+/// This function lists the classifications we support from lowest to
+/// highest, each with its rank, so that clients needn't hard code the
+/// order.
+async fn handle_classifications() -> (StatusCode, String) {
+    event!(Level::DEBUG, "Received the Classifications Request");
+
+    let body: Vec<serde_json::Value> = Classification::iter()
+        .enumerate()
+        .map(|(rank, classification)| serde_json::json!({
+            "name": classification,
+            "rank": rank,
+        }))
+        .collect();
+
+    (StatusCode::OK, serde_json::to_string(&body).unwrap())
+} // end handle_classifications
+
+/// This function returns a strong ETag for the given body, made from a
+/// truncated SHA-256 hash of it.
+fn body_etag(body: &str) -> String {
+    let digest = Sha256::digest(body.as_bytes());
+    format!("\"{}\"", hex::encode(&digest[..ETAG_HASH_BYTES]))
+}
+
+/// This function returns true if the request's If-None-Match header
+/// lists the given ETag, or is the wildcard.
+fn etag_matches(headers: &HeaderMap, etag: &str) -> bool {
+    headers.get_all(IF_NONE_MATCH).iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .map(|candidate| candidate.trim().trim_start_matches("W/"))
+        .any(|candidate| candidate == "*" || candidate == etag)
 }
 
+/// This function constructs the chat message a send request would
+/// create in the requested room.
+fn build_posted_message(
+    request: &messages::SendChatMessageRequest
+) -> messages::ChatMessageSchema {
+    messages::ChatMessageSchema {
+        classification: request.classification.clone(),
+        domain_id:      request.domain_id.clone(),
+        geo_tags:       None,
+        id:             Uuid::new_v4().to_string(),
+        room_name:      request.room_name.clone(),
+        sender:         request.nickname.clone(),
+        text:           request.message.clone(),
+        thread_id:      None,
+        timestamp:      Timestamp::now(),
+        user_id:        Uuid::new_v4().to_string(),
+        private:        false,
+        sequence:       None,
+        score:          None,
+        sender_meta:    None,
+    }
+} // end build_posted_message
+
 async fn handle_post_chat_message(
+    State(state): State<ServerState>,
     headers:    HeaderMap,
     payload:    String,
 ) -> (StatusCode, String) {
@@ -203,20 +771,104 @@ async fn handle_post_chat_message(
         event!(Level::DEBUG, "{}", key_value.to_str().unwrap())
     }
 
+    // A retried request carrying a key we have already seen gets the
+    // original response, rather than posting the message a second time.
+    let idempotency_key = headers.get(IDEMPOTENCY_KEY_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .map(String::from);
+
+    let Some(key) = idempotency_key else {
+        return post_chat_message(&state, payload);
+    };
+
+    let (response, seen) = state.idempotent_response(key.clone());
+
+    if seen {
+        event!(Level::DEBUG, "Replaying the response for idempotency key {}", key);
+    }
+
+    response.get_or_init(|| async { post_chat_message(&state, payload) }).await.clone()
+} // end handle_post_chat_message
+
+/// This function applies the server's policies to a send request,
+/// returning the 400 body describing the first violation.
+fn validate_send_request(
+    state:      &ServerState,
+    request:    &messages::SendChatMessageRequest,
+) -> Result<(), messages::ErrorCode400> {
+    if state.args.reserved_nicknames.iter()
+        .any(|reserved| reserved.eq_ignore_ascii_case(&request.nickname)) {
+        return Err(messages::ErrorCode400 {
+            field_errors: vec![messages::FieldErrorSchema {
+                field_name:     String::from("nickname"),
+                message:        String::from("Nickname is reserved"),
+                message_code:   String::from("NicknameIsReserved"),
+                rejected_value: request.nickname.clone(),
+                ..Default::default()
+            }],
+            message: String::from("The request contained 1 or more field validation errors."),
+            ..Default::default()
+        });
+    }
+
+    if request.message.len() > state.args.max_message_len {
+        // Echo back only the start of an oversized message.
+        let preview: String = request.message.chars().take(REJECTED_MESSAGE_PREVIEW_CHARS).collect();
+
+        return Err(messages::ErrorCode400 {
+            field_errors: vec![messages::FieldErrorSchema {
+                field_name:     String::from("message"),
+                message:        format!("Message is longer than {} bytes", state.args.max_message_len),
+                message_code:   String::from("MessageTooLong"),
+                rejected_value: preview,
+                ..Default::default()
+            }],
+            message: String::from("The request contained 1 or more field validation errors."),
+            ..Default::default()
+        });
+    }
+
+    Ok(())
+} // end validate_send_request
+
+fn post_chat_message(
+    state:      &ServerState,
+    payload:    String,
+) -> (StatusCode, String) {
+
+    if payload.is_empty() {
+        return bad_request(messages::ErrorCode400::body_missing());
+    }
 
     // Attempt to deserialize the request paylod.
-    let request = messages::SendChatMessageRequest::from_string(payload.clone());
+    let request = match messages::SendChatMessageRequest::try_from_string(payload.clone()) {
+        Ok(request) => request,
+        Err(e) => return bad_request(e.into()),
+    };
     event!(Level::DEBUG, "Received new message request from {}: {}", request.nickname, payload);
 
+    if let Err(body) = validate_send_request(state, &request) {
+        return bad_request(body);
+    }
+
     
     //let num = rand::thread_rng().gen_range(0..2);
     let num = 0;
     
     match num {
-        // 204 Successful case.
+        // 204 or 201 Successful case.
         0 => {
-            event!(Level::DEBUG, "{}", serde_json::to_string("Hello").unwrap());
-            (StatusCode::NO_CONTENT, serde_json::to_string("Hello").unwrap())
+            let message = build_posted_message(&request);
+            state.publish(message.clone());
+
+            // Some versions of the API respond with the created message
+            // rather than an empty body.
+            if state.args.post_success_status == StatusCode::CREATED.as_u16() {
+                event!(Level::DEBUG, "{}", message);
+                (StatusCode::CREATED, message.to_string())
+            } else {
+                (StatusCode::NO_CONTENT, String::new())
+            }
         },
         // 400 Bad Request case.
         1 => {
@@ -226,7 +878,7 @@ async fn handle_post_chat_message(
                     message:            String::from("Room name not found"),
                     message_arguments:   vec!(String::from("I don't know what to put here")),
                     message_code:        String::from("I don't know what to put here"),
-                    rejected_value:      String::from(request.room_name)
+                    rejected_value:      request.room_name
                 }],
                 ..Default::default()
             };
@@ -242,10 +894,178 @@ async fn handle_post_chat_message(
     }
 }
 
+async fn handle_post_bulk_messages(
+    State(state): State<ServerState>,
+    headers:    HeaderMap,
+    payload:    String,
+) -> (StatusCode, String) {
+
+    if headers.contains_key("api-key") {
+        let key_value = headers.get("api-key").unwrap();
+        event!(Level::DEBUG, "{}", key_value.to_str().unwrap())
+    }
+
+    if payload.is_empty() {
+        return bad_request(messages::ErrorCode400::body_missing());
+    }
+
+    // Only the outer array has to parse.  Each entry is checked on its
+    // own, so that one bad entry doesn't reject the whole batch.
+    let entries = match serde_json::from_str::<Vec<serde_json::Value>>(&payload)
+        .context("Unable to parse the bulk request as a JSON array") {
+        Ok(entries) => entries,
+        Err(e) => return bad_request(e.into()),
+    };
+    event!(Level::DEBUG, "Received a bulk request of {} messages", entries.len());
+
+    let mut body = messages::BulkSendChatMessagesResponse::default();
+
+    for (index, entry) in entries.into_iter().enumerate() {
+        let result = messages::SendChatMessageRequest::try_from_string(entry.to_string())
+            .map_err(messages::ErrorCode400::from)
+            .and_then(|request| validate_send_request(&state, &request).map(|()| request));
+
+        match result {
+            Ok(request) => {
+                state.publish(build_posted_message(&request));
+                body.accepted += 1;
+            }
+            Err(error) => {
+                body.rejected += 1;
+                body.errors.push(messages::BulkSendError {
+                    index,
+                    field_errors:   error.field_errors,
+                    message:        error.message,
+                });
+            }
+        }
+    }
+
+    event!(Level::DEBUG, "{}", body);
+    (StatusCode::OK, body.to_string())
+} // end handle_post_bulk_messages
+
+/// This struct describes the query parameters accepted by the
+/// search messages route.
+#[derive(Deserialize)]
+struct SearchQuery {
+    // When set, the response includes the number of matches for
+    // each keyword.
+    #[serde(default)]
+    breakdown:  bool,
+
+    // When set, each message in the response includes its relevance
+    // score.
+    #[serde(default)]
+    scores:     bool,
+
+    // A comma separated list of the geo-tag fields to return, leaving
+    // out the rest.
+    geo_fields: Option<String>,
+}
+
+/// This function checks every field of a search request, returning the
+/// offset its cursor continues from, or an error listing all of the
+/// fields that are invalid.
+fn validate_search_request(
+    state:      &ServerState,
+    request:    &messages::SearchChatMessagesRequest,
+) -> Result<usize, messages::ErrorCode400> {
+    let mut field_errors: Vec<messages::FieldErrorSchema> = Vec::new();
+
+    match &request.keyword_filter {
+        Some(filter) if filter.query.split(' ').all(str::is_empty)
+            && state.args.empty_query_behavior == EmptyQueryBehavior::Reject => {
+            field_errors.push(messages::FieldErrorSchema {
+                field_name:     String::from("keywordFilter"),
+                message:        String::from("Query must contain at least one keyword"),
+                message_code:   String::from("ChatMessageSearchQueryStringIsEmpty"),
+                rejected_value: filter.query.clone(),
+                ..Default::default()
+            });
+        }
+        Some(filter) => {
+            // Like the real API, refuse terms that start with a wildcard.
+            if let Some(term) = filter.query.split(' ')
+                .find(|term| term.starts_with('*') || term.starts_with('?')) {
+                field_errors.push(messages::FieldErrorSchema {
+                    field_name:     String::from("keywordFilter"),
+                    message:        String::from("'*' or '?' not allowed as first character of a term"),
+                    message_code:   String::from("ChatMessageSearchQueryStringIsInvalid"),
+                    rejected_value: String::from(term),
+                    ..Default::default()
+                });
+            }
+        }
+        None => field_errors.push(messages::FieldErrorSchema {
+            field_name:     String::from("keywordFilter"),
+            message:        String::from("Keyword filter is required"),
+            message_code:   String::from("KeywordFilterIsRequired"),
+            ..Default::default()
+        }),
+    }
+
+    // Each field may only be sorted on once, since a second order on the
+    // same field would contradict the first.
+    if let Some(sort) = &request.sort {
+        let duplicate = sort.orders.iter().enumerate()
+            .find(|(index, (_, field))| sort.orders[..*index].iter().any(|(_, earlier)| earlier == field));
+
+        if let Some((_, (_, field))) = duplicate {
+            field_errors.push(messages::FieldErrorSchema {
+                field_name:     String::from("sort"),
+                message:        String::from("Sort fields must not be repeated"),
+                message_code:   String::from("SortFieldIsDuplicated"),
+                rejected_value: field.to_string(),
+                ..Default::default()
+            });
+        }
+    }
+
+    let offset = match &request.cursor {
+        Some(cursor) => match state.cursor_signer.decode(cursor) {
+            Some(offset) => offset as usize,
+            None => {
+                field_errors.push(messages::FieldErrorSchema {
+                    field_name:     String::from("cursor"),
+                    message:        String::from("Cursor is invalid"),
+                    message_code:   String::from("CursorIsInvalid"),
+                    rejected_value: cursor.clone(),
+                    ..Default::default()
+                });
+                0
+            }
+        },
+        None => 0,
+    };
+
+    if Classification::from_str(&request.user_high_classification).is_err() {
+        field_errors.push(messages::FieldErrorSchema {
+            field_name:     String::from("UserHighClassification"),
+            message:        String::from("Unknown classification"),
+            message_code:   String::from("ClassificationIsInvalid"),
+            rejected_value: request.user_high_classification.clone(),
+            ..Default::default()
+        });
+    }
+
+    if !field_errors.is_empty() {
+        return Err(messages::ErrorCode400 {
+            field_errors,
+            message: String::from("The request contained 1 or more field validation errors."),
+            ..Default::default()
+        });
+    }
+
+    Ok(offset)
+} // end validate_search_request
+
 async fn handle_search_messages(
+    State(state): State<ServerState>,
+    Query(query): Query<SearchQuery>,
     headers:    HeaderMap,
     payload:    String
-) -> (StatusCode, String) {
+) -> Response {
 
     // Attempt to deserialize the request paylod.
     event!(Level::DEBUG, "Received Search Messages request: {}", payload);
@@ -255,30 +1075,135 @@ async fn handle_search_messages(
         event!(Level::DEBUG, "{}", key_value.to_str().unwrap())
     }
 
-    let request = messages::SearchChatMessagesRequest::from_string(payload);
-    
+    if payload.is_empty() {
+        return bad_request(messages::ErrorCode400::body_missing()).into_response();
+    }
+
+    let request = match messages::SearchChatMessagesRequest::try_from_string(payload) {
+        Ok(request) => request,
+        Err(e) => return bad_request(e.into()).into_response(),
+    };
+
+    // A cursor continues an earlier search, from the offset it holds.
+    let offset = match validate_search_request(&state, &request) {
+        Ok(offset) => offset,
+        Err(body) => return bad_request(body).into_response(),
+    };
+
+    // Refuse to return data above the user's clearance, which may be any
+    // of the classifications messages are generated with.  The
+    // classification is known to be valid by now.
+    let clearance = Classification::from_str(&request.user_high_classification).unwrap();
+    let data_classification = state.args.highest_classification();
+
+    if clearance < data_classification {
+        let body = messages::ErrorCode403 {
+            message: format!(
+                "The user's classification {} is lower than the data's classification {}.",
+                clearance,
+                data_classification),
+            ..Default::default()
+        };
+
+        event!(Level::DEBUG, "{}", body);
+        return (StatusCode::FORBIDDEN, body.to_string()).into_response();
+    }
+
     //let num = rand::thread_rng().gen_range(0..2);
     let num = 0;
     
     match num {
         // 200 Successful case.
         0 => {
-            let search_results = search_messages(request.keyword_filter.unwrap().query);
+            let keywords = request.keyword_filter.unwrap().query;
+            let (mut scored, keyword_counts) =
+                search_messages(
+                    &state,
+                    keywords.clone(),
+                    request.room_filter.as_ref(),
+                    request.time_filter.as_ref());
+
+            let split_keywords = split_query(&keywords);
+
+            // Order the results by relevance if that's the first sort
+            // order requested, breaking ties with the oldest first
+            // whichever way the scores run.
+            let relevance_order = request.sort.as_ref()
+                .and_then(|sort| sort.orders.first())
+                .filter(|(_, field)| *field == messages::SortField::RELEVANCE)
+                .map(|(direction, _)| direction);
+
+            if let Some(direction) = relevance_order {
+                scored.sort_by(|(a_score, a), (b_score, b)| {
+                    let by_score = match direction {
+                        messages::SortDirection::ASC => a_score.cmp(b_score),
+                        messages::SortDirection::DESC => b_score.cmp(a_score),
+                    };
+
+                    by_score.then_with(|| a.timestamp.cmp(&b.timestamp))
+                });
+            }
+
+            let mut search_results: Vec<ChatMessageSchema> = scored.into_iter()
+                .map(|(score, mut message)| {
+                    if query.scores {
+                        message.score = Some(score);
+                    }
+
+                    message
+                })
+                .collect();
+
+            if request.highlight_results == Some(true) {
+                for message in search_results.iter_mut() {
+                    message.text = highlight_keywords(
+                        &message.text,
+                        &split_keywords,
+                        &state.args.highlight_prefix,
+                        &state.args.highlight_suffix);
+                }
+            }
             let total: i32 = search_results.len() as i32;
 
+            // Return one page of the results, with a cursor for the next
+            // page if there is one.
+            search_results.drain(..offset.min(search_results.len()));
+
+            let mut next_cursor_mark = None;
+
+            if let Some(limit) = request.limit.filter(|&limit| limit > 0) {
+                let limit = limit as usize;
+
+                if search_results.len() > limit {
+                    search_results.truncate(limit);
+                    next_cursor_mark = Some(state.cursor_signer.encode((offset + limit) as u64));
+                }
+            }
+
+            // The response as a whole is marked with the highest
+            // classification of the messages it holds.
+            let classification = highest_classification(&search_results)
+                .unwrap_or(state.args.classification);
+
             let body = messages::SearchChatMessagesResponse {
-                classification:     String::from(UNCLASSIFIED_STRING),
+                classification:     classification.to_string(),
+                keyword_counts:     if query.breakdown { Some(keyword_counts) } else { None },
                 messages:           Some(search_results),
-                next_cursor_mark:     None,
+                next_cursor_mark,
                 search_time_filter:    TimeFilterResponse {
-                    end_date_time:    Utc::now().to_rfc3339()
+                    end_date_time:    Timestamp::now()
                 },
-                total:              total,
+                total,
             };
 
 
             event!(Level::DEBUG, "{}", serde_json::to_string(&body).unwrap());
-            (StatusCode::OK, serde_json::to_string(&body).unwrap())
+            let (response_headers, body) = match query.geo_fields.as_deref() {
+                Some(selection) => negotiate_body(&headers, "SearchChatMessagesResponse",
+                    &projection::project_geo_tags(&body, selection)),
+                None => negotiate_body(&headers, "SearchChatMessagesResponse", &body),
+            };
+            (StatusCode::OK, response_headers, body).into_response()
         },
         // 400 Bad Request case.
         1 => {
@@ -298,57 +1223,543 @@ async fn handle_search_messages(
             };
 
             event!(Level::DEBUG, "{}", serde_json::to_string(&body).unwrap());
-            (StatusCode::BAD_REQUEST, serde_json::to_string(&body).unwrap())
+            (StatusCode::BAD_REQUEST, serde_json::to_string(&body).unwrap()).into_response()
         },
         // 429 Rate Exceeded case.
         _ => {
             event!(Level::DEBUG, "{}", serde_json::to_string("Rate Exceeded").unwrap());
-            (StatusCode::TOO_MANY_REQUESTS, serde_json::to_string("Rate Exceeded").unwrap())
+            (StatusCode::TOO_MANY_REQUESTS, serde_json::to_string("Rate Exceeded").unwrap()).into_response()
         },
     }
 } // end handle_search_messages
 
-async fn handle_public_key_request() -> String {
+/// This function returns the realm's public key, in the form served by
+/// the real Keycloak instance.  The key is the one the token route signs
+/// with, so that the tokens it issues verify against it.
+async fn handle_public_key_request(State(state): State<ServerState>) -> String {
     event!(Level::DEBUG, "Received the Get Public Key Request");
 
-    String::from("{\"realm\":\"fmv\",\"public_key\":\"MIIBIjANBgkqhkiG9w0BAQEFAAOCAQ8AMIIBCgKCAQEAzq/jsj5MTmOA9sW4YBJpv16yLPvznKLj3UqNXQ17WhukP5wu6GQyHMUSqNV8CAqGEA8TJpoQcpTCs8iaKxpfF1yORKdeuvCa/aJZpOw6TwsJZa1OWLONyJnOuPeZZNDUn+D7as+tS9ws7UP3AtROO8hkMS7+B3C90eXTWhZnkzEDSfDmfUxPMvYH/5yGUI4AtzbAGPMwiDOXOguXUSkV5TP7RXTZqrgHp3yvzBsbaWtjW9r4tfzXRHuGFXhlEgBdsBIzupaXrpfqIjHQXDhJ1NnI6KOQUTDi5t3VOhfZ8z6WXMPdqi/pvyzTenAshvoTR2rEti6KyLqwTdW6y1KFVQIDAQAB\",\"token-service\":\"https://app.fmvedgeview.net/keycloak/auth/realms/fmv/protocol/openid-connect\",\"account-service\":\"https://app.fmvedgeview.net/keycloak/auth/realms\",\"tokens-not-before\":0}")
+    serde_json::json!({
+        "realm":            DEFAULT_TOKEN_REALM,
+        "public_key":       state.token_issuer.public_key(),
+        "token-service":    "https://app.fmvedgeview.net/keycloak/auth/realms/fmv/protocol/openid-connect",
+        "account-service":  "https://app.fmvedgeview.net/keycloak/auth/realms",
+        "tokens-not-before": 0,
+    }).to_string()
 } // end handle_public_key_request
 
+/// This struct describes the body of a request for an access token.  Each
+/// of the claims may be left out.
+#[derive(Deserialize)]
+struct TokenRequest {
+    #[serde(default = "default_token_subject")]
+    sub:        String,
+
+    #[serde(default = "default_token_realm")]
+    realm:      String,
+
+    // The number of seconds until the token expires.
+    #[serde(default = "default_token_expires_in")]
+    expires_in: u64,
+}
+
+fn default_token_subject() -> String {
+    String::from(DEFAULT_TOKEN_SUBJECT)
+}
+
+fn default_token_realm() -> String {
+    String::from(DEFAULT_TOKEN_REALM)
+}
+
+fn default_token_expires_in() -> u64 {
+    DEFAULT_TOKEN_EXPIRES_IN_SECS
+}
+
+/// This function issues an RS256 signed access token, responding the way
+/// Keycloak's token endpoint does.  An empty body gets a token with the
+/// default claims.
+async fn handle_token_request(
+    State(state): State<ServerState>,
+    payload:    String,
+) -> (StatusCode, String) {
+    event!(Level::DEBUG, "Received the Token Request: {}", payload);
+
+    let body = if payload.trim().is_empty() { "{}" } else { &payload };
+
+    let request = match serde_json::from_str::<TokenRequest>(body)
+        .context("Unable to parse the token request") {
+        Ok(request) => request,
+        Err(e) => return bad_request(e.into()),
+    };
+
+    let issued_at = Utc::now().timestamp() as u64;
+
+    let claims = token::TokenClaims {
+        sub:    request.sub,
+        realm:  request.realm,
+        iat:    issued_at,
+        exp:    issued_at + request.expires_in,
+    };
+
+    let body = serde_json::json!({
+        "access_token": state.token_issuer.issue(&claims),
+        "expires_in":   request.expires_in,
+        "token_type":   "Bearer",
+    });
+
+    (StatusCode::OK, body.to_string())
+} // end handle_token_request
+
+/// This function sends a chat message to the WebSocket client, splitting
+/// it into fragments when it is longer than the configured fragment size.
+async fn send_chat_message(
+    socket:     &mut axum::extract::ws::WebSocket,
+    state:      &ServerState,
+    message:    &ChatMessageSchema,
+) -> Result<(), axum::Error> {
+    let json = message.try_to_json().unwrap();
+
+    let frames: Vec<String> = match state.args.ws_fragment_bytes {
+        Some(bytes) if json.len() > bytes.get() => {
+            fragment::split_message(&message.id, &json, bytes.get()).iter()
+                .map(|fragment| serde_json::to_string(fragment).unwrap())
+                .collect()
+        }
+        _ => vec!(json),
+    };
+
+    // Refuse to send a message the client has told us it can't accept,
+    // rather than letting the connection fail in a way that is hard to
+    // diagnose.
+    if let Some(limit) = state.args.ws_frame_limit() {
+        if let Some(frame) = frames.iter().find(|frame| frame.len() > limit) {
+            event!(Level::ERROR,
+                "Error - message {} was not sent: its {} byte frame exceeds the {} byte WebSocket limit",
+                message.id, frame.len(), limit);
+            return Ok(());
+        }
+    }
+
+    for frame in frames {
+        socket.send(Message::Text(frame)).await?;
+    }
+
+    Ok(())
+} // end send_chat_message
+
+/// This function sends the given messages to the client.  When batching
+/// is enabled they go in a single frame holding a JSON array, which is
+/// never fragmented; otherwise each is sent on its own.
+async fn send_chat_messages(
+    socket:     &mut axum::extract::ws::WebSocket,
+    state:      &ServerState,
+    messages:   &[ChatMessageSchema],
+) -> Result<(), axum::Error> {
+    if state.args.ws_batch_size == 1 {
+        for message in messages {
+            send_chat_message(socket, state, message).await?;
+        }
+
+        return Ok(());
+    }
+
+    let frame = serde_json::to_string(messages).unwrap();
+
+    if let Some(limit) = state.args.ws_frame_limit() {
+        if frame.len() > limit {
+            event!(Level::ERROR,
+                "Error - a batch of {} messages was not sent: its {} byte frame exceeds the {} byte WebSocket limit",
+                messages.len(), frame.len(), limit);
+            return Ok(());
+        }
+    }
+
+    socket.send(Message::Text(frame)).await
+} // end send_chat_messages
+
 async fn serve_ws_single_room(
-    mut socket: axum::extract::ws::WebSocket
+    mut socket: axum::extract::ws::WebSocket,
+    state:      ServerState,
+    query:      WebSocketQuery,
 ) {
-    loop {
-        // We will periodically send messages to the client to simulate events
-        // taking place within a ChatSurfer chat room.
-        thread::sleep(Duration::from_secs(SECONDS_BETWEEN_WEBSOCKET_UPDATE));
+    match socket.protocol() {
+        Some(protocol) => {
+            event!(Level::DEBUG, "Negotiated WebSocket subprotocol {:?}", protocol);
+        }
+        None => {
+            event!(Level::DEBUG, "No WebSocket subprotocol negotiated.");
+        }
+    }
+
+    let mut posted_messages = state.broadcast.subscribe();
+
+    // This identifies the connection in the capture file.  Unlike the
+    // stream id, it isn't carried over when a stream is resumed.
+    let connection_id = Uuid::new_v4().to_string();
+
+    // The generated messages come from the room's shared feed, so that
+    // every subscriber to the room sees the same ones.
+    let mut room_feed = rooms::subscribe(&state, &format!("{}/{}", TEST_DOMAIN_ID, TEST_ROOM_NAME));
+
+    // Only forward the messages matching the client's filter, if it gave
+    // one.  An expression we can't parse ends the connection.
+    let filter = match query.filter.as_deref().map(filter::MessageFilter::parse) {
+        Some(Ok(filter)) => Some(filter),
+        Some(Err(reason)) => {
+            event!(Level::DEBUG, "Rejecting the WebSocket filter: {}", reason);
+
+            let _ = socket.send(Message::Close(Some(CloseFrame {
+                code:   close_code::POLICY,
+                reason: reason.into(),
+            }))).await;
+            return;
+        }
+        None => None,
+    };
+    let is_wanted = |message: &ChatMessageSchema|
+        filter.as_ref().is_none_or(|filter| filter.matches(message));
+
+    // Continue the stream the client was on before, if it asked to and
+    // we still have it, sending the messages it missed.
+    let resume = query.resume.as_deref()
+        .and_then(|token| resume::ResumeToken::decode(token, &state.cursor_signer));
+    let failure = match &resume {
+        Some(_) => "The stream is no longer buffered, so a new stream has started",
+        None => "The resume token is invalid, so a new stream has started",
+    };
+    let (stream_id, missed_messages) = state.streams.lock().unwrap().open(resume);
+
+    // Tell a client whose stream couldn't be continued, so that it doesn't
+    // mistake the new one for a continuation.
+    let missed_messages = match missed_messages {
+        Some(missed_messages) => missed_messages,
+        None if query.resume.is_some() => {
+            event!(Level::DEBUG, "Unable to resume the stream: {}", failure);
+
+            if let Err(e) = socket.send(Message::Text(
+                serde_json::to_string(&resume::ResumeFailedFrame::new(failure)).unwrap()
+            )).await {
+                event!(Level::ERROR, "Error - could not tell the client its stream wasn't resumed: {}", e);
+                return;
+            }
+            Vec::new()
+        }
+        None => Vec::new(),
+    };
+    event!(Level::DEBUG, "Serving stream {} with {} missed messages",
+        stream_id, missed_messages.len());
+
+    let mut last_sent: Option<u64> = None;
+
+    if !missed_messages.is_empty() {
+        if let Err(e) = send_chat_messages(&mut socket, &state, &missed_messages).await {
+            event!(Level::ERROR, "Error - could not replay messages to the client: {}", e);
+            return;
+        }
+        last_sent = missed_messages.last().unwrap().sequence;
+    }
+
+    // Send the room's most recent messages, oldest first, if the client
+    // asked for them.  They are numbered like any other message on the
+    // stream.
+    if let Some(count) = query.backlog.filter(|&count| count > 0) {
+        let mut backlog: Vec<ChatMessageSchema> = state.stored_messages().into_iter()
+            .filter(|message| message.domain_id == TEST_DOMAIN_ID
+                && message.room_name == TEST_ROOM_NAME
+                && is_wanted(message))
+            .collect();
+        backlog.drain(..backlog.len().saturating_sub(count));
+
+        let backlog: Vec<ChatMessageSchema> = {
+            let mut streams = state.streams.lock().unwrap();
+            backlog.into_iter().map(|message| streams.push(&stream_id, message)).collect()
+        };
+
+        if !backlog.is_empty() {
+            if let Err(e) = send_chat_messages(&mut socket, &state, &backlog).await {
+                event!(Level::ERROR, "Error - could not send the backlog to the client: {}", e);
+                return;
+            }
+            last_sent = backlog.last().unwrap().sequence;
+        }
+    }
+
+    let mut resume_interval = tokio::time::interval(
+        Duration::from_secs(SECONDS_BETWEEN_RESUME_TOKENS));
+
+    let mut heartbeat_interval = tokio::time::interval(
+        Duration::from_secs(state.args.heartbeat_interval_secs));
+    let mut heartbeat_sequence: u64 = 0;
+
+    // The number of messages to send before closing the connection, if
+    // connections are being recycled.
+    let recycle_after = state.args.ws_recycle_after.as_ref()
+        .map(|range| state.random_range(range.min..=range.max));
+    let mut messages_sent: u64 = 0;
 
-        // Send a randomly generated chat message to the client.
+    let mut inbound_limiter = state.args.ws_inbound_rate.map(inbound::InboundLimiter::new);
 
-        let random_seed = rand::random::<i32>();
+    loop {
+        // We will periodically send messages to the client to simulate events
+        // taking place within a ChatSurfer chat room.  Messages posted to the
+        // room are forwarded as soon as they arrive, and frames from the
+        // client are captured, if asked to, until it closes the connection.
+        let messages: Vec<ChatMessageSchema> = tokio::select! {
+            _ = heartbeat_interval.tick(), if query.heartbeat => {
+                let frame = heartbeat::HeartbeatFrame::new(
+                    Utc::now().to_rfc3339(),
+                    heartbeat_sequence);
+                heartbeat_sequence += 1;
+
+                if let Err(e) = socket.send(Message::Text(
+                    serde_json::to_string(&frame).unwrap()
+                )).await {
+                    event!(Level::ERROR, "Error - could not send a heartbeat to the client: {}", e);
+                    break;
+                }
+
+                continue;
+            }
+            _ = resume_interval.tick(), if last_sent.is_some() => {
+                if let Err(e) = send_resume_frame(&mut socket, &state, &stream_id, last_sent.unwrap()).await {
+                    event!(Level::ERROR, "Error - could not send a resume token to the client: {}", e);
+                    break;
+                }
 
-        let message = build_chat_message(
-            random_seed.clone(),
-            "Austin",
-            random_seed.clone().to_string().as_str()
-        );
+                continue;
+            }
+            generated = room_feed.recv() => {
+                let generated = match generated {
+                    Ok(generated) => generated,
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                        event!(Level::ERROR, "Error - missed {} pushes of generated messages", skipped);
+                        continue;
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                };
+
+                // Send the room's latest batch of generated messages to the
+                // client, which is a single message unless batching.
+                let mut batch = Vec::new();
+
+                for message in generated {
+                    if !is_wanted(&message) {
+                        continue;
+                    }
+
+                    let message = state.streams.lock().unwrap().push(&stream_id, message);
+
+                    // Simulate a lossy link by occasionally dropping a
+                    // generated message after it has used up its sequence
+                    // number, leaving a gap for the client to detect.  The
+                    // message stays buffered, so resuming recovers it, but
+                    // only until RESUME_BUFFER_CAPACITY later messages on
+                    // the stream have pushed it out.
+                    if state.random_bool(state.args.ws_loss_rate) {
+                        event!(Level::DEBUG, "Dropping message {} with sequence number {:?}",
+                            message.id, message.sequence);
+                        continue;
+                    }
+
+                    // Simulate at-least-once delivery by occasionally
+                    // sending a message twice in a row.  Both copies have
+                    // the same id and sequence number.  Skipping the draw
+                    // when disabled keeps seeded streams as they were.
+                    if state.args.ws_dup_rate > 0.0 && state.random_bool(state.args.ws_dup_rate) {
+                        event!(Level::DEBUG, "Duplicating message {} with sequence number {:?}",
+                            message.id, message.sequence);
+                        batch.push(message.clone());
+                    }
+
+                    batch.push(message);
+                }
+
+                if batch.is_empty() {
+                    continue;
+                }
+
+                batch
+            }
+            received = socket.recv() => {
+                match received {
+                    Some(Ok(Message::Close(_))) | None => {
+                        event!(Level::DEBUG, "The client closed the connection.");
+                        break;
+                    }
+                    Some(Ok(message)) => {
+                        let verdict = inbound_limiter.as_mut()
+                            .map_or(inbound::InboundVerdict::Accept, inbound::InboundLimiter::check);
+
+                        match verdict {
+                            inbound::InboundVerdict::Accept => (),
+                            inbound::InboundVerdict::Warn => {
+                                event!(Level::DEBUG, "The client is sending frames too quickly.");
+
+                                let frame = inbound::ErrorFrame::new(format!(
+                                    "Too many frames, the limit is {} per second",
+                                    state.args.ws_inbound_rate.unwrap()));
+
+                                if let Err(e) = socket.send(Message::Text(
+                                    serde_json::to_string(&frame).unwrap()
+                                )).await {
+                                    event!(Level::ERROR, "Error - could not send an error to the client: {}", e);
+                                    break;
+                                }
+                                continue;
+                            }
+                            inbound::InboundVerdict::Drop => continue,
+                            inbound::InboundVerdict::Close => {
+                                event!(Level::DEBUG, "Closing the connection of a client flooding the server.");
+
+                                let _ = socket.send(Message::Close(Some(CloseFrame {
+                                    code:   close_code::POLICY,
+                                    reason: "Too many frames".into(),
+                                }))).await;
+                                break;
+                            }
+                        }
+
+                        if let Some(capture) = &state.capture {
+                            capture.record(&connection_id, &message);
+                        }
+                        continue;
+                    }
+                    Some(Err(e)) => {
+                        event!(Level::ERROR, "Error - could not receive from the client: {}", e);
+                        break;
+                    }
+                }
+            }
+            posted = posted_messages.recv() => {
+                match posted {
+                    Ok(message) if message.domain_id == TEST_DOMAIN_ID
+                        && message.room_name == TEST_ROOM_NAME
+                        && is_wanted(&message) => {
+                        vec!(state.streams.lock().unwrap().push(&stream_id, message))
+                    }
+                    Ok(_) => continue,
+                    Err(e) => {
+                        event!(Level::ERROR, "Error - missed posted messages: {}", e);
+                        continue;
+                    }
+                }
+            }
+        };
 
-        match socket.send(Message::Text(
-            message.try_to_json().unwrap()
-        )).await {
+        match send_chat_messages(&mut socket, &state, &messages).await {
             Ok(()) => {
-                event!(Level::DEBUG, "Successfully sent message {} to client.", random_seed);
+                event!(Level::DEBUG, "Successfully sent {} messages to client.", messages.len());
+                last_sent = messages.last().unwrap().sequence;
+                messages_sent += messages.len() as u64;
             }
             Err(e) => {
                 event!(Level::ERROR, "Error - could not send the response to the client: {}", e);
+                break;
+            }
+        }
+
+        // Close a recycled connection as a restarting server would, having
+        // first told the client where to resume from.
+        if recycle_after.is_some_and(|count| messages_sent >= count) {
+            event!(Level::DEBUG, "Recycling the connection after {} messages", messages_sent);
+
+            if let Err(e) = send_resume_frame(&mut socket, &state, &stream_id, last_sent.unwrap()).await {
+                event!(Level::ERROR, "Error - could not send a resume token to the client: {}", e);
+                break;
             }
+
+            let _ = socket.send(Message::Close(Some(CloseFrame {
+                code:   close_code::RESTART,
+                reason: "Recycling the connection".into(),
+            }))).await;
+            break;
         }
     }
 } // end serve_ws_single_room
 
+/// This function sends the client a token for resuming the stream after
+/// the message with the given sequence number.
+async fn send_resume_frame(
+    socket:     &mut axum::extract::ws::WebSocket,
+    state:      &ServerState,
+    stream_id:  &str,
+    sequence:   u64,
+) -> Result<(), axum::Error> {
+    let token = resume::ResumeToken {
+        stream_id:  String::from(stream_id),
+        sequence,
+    };
+
+    socket.send(Message::Text(
+        serde_json::to_string(&resume::ResumeFrame::new(&token, &state.cursor_signer)).unwrap()
+    )).await
+} // end send_resume_frame
+
+/// This struct describes the query parameters accepted by the
+/// WebSocket routes.
+#[derive(Deserialize)]
+struct WebSocketQuery {
+    // When set, heartbeat frames are interleaved with the chat messages.
+    #[serde(default)]
+    heartbeat:  bool,
+
+    // A token from an earlier connection's resume frames, continuing
+    // that connection's stream.
+    resume:     Option<String>,
+
+    // An expression such as sender=Austin or text~keyword selecting the
+    // chat messages to forward.
+    filter:     Option<String>,
+
+    // The API key, for clients that can't set headers on the upgrade
+    // request.
+    token:      Option<String>,
+
+    // The number of the room's most recently stored messages to send
+    // before the live stream.
+    backlog:    Option<usize>,
+}
+
 async fn serve_ws_single_room_upgrade_handler(
+    State(state): State<ServerState>,
+    Query(query): Query<WebSocketQuery>,
+    headers:    HeaderMap,
     ws: WebSocketUpgrade,
 ) -> Response {
-    ws.on_upgrade(|socket| serve_ws_single_room(socket))
+    // Refuse the upgrade unless the client presents the API key, in
+    // either the api-key header or the token query parameter.
+    if state.args.require_ws_auth {
+        let header_key = headers.get("api-key").and_then(|value| value.to_str().ok());
+
+        if header_key != Some(TEST_API_KEY) && query.token.as_deref() != Some(TEST_API_KEY) {
+            let body = messages::ErrorCode401 {
+                message: String::from("A valid API key is required to open a WebSocket connection."),
+                ..Default::default()
+            };
+
+            event!(Level::DEBUG, "{}", body);
+            return (StatusCode::UNAUTHORIZED, body.to_string()).into_response();
+        }
+    }
+
+    // Select the first of our allowed subprotocols that the client also
+    // requested.  If none of them match, the upgrade still succeeds, just
+    // without a Sec-WebSocket-Protocol header in the response.
+    let mut ws = ws.protocols(state.args.ws_subprotocols.clone());
+
+    if let Some(max) = state.args.ws_max_frame_bytes {
+        ws = ws.max_frame_size(max);
+    }
+
+    if let Some(max) = state.args.ws_max_message_bytes {
+        ws = ws.max_message_size(max);
+    }
+
+    ws.on_upgrade(move |socket| async move {
+        state.ws_connections.fetch_add(1, Ordering::Relaxed);
+        serve_ws_single_room(socket, state.clone(), query).await;
+        state.ws_connections.fetch_sub(1, Ordering::Relaxed);
+    })
 } // end serve_ws_single_room_upgrade_handler
 
 /*
@@ -367,91 +1778,1253 @@ struct Args {
     // from a client.
     #[arg(long = "client_port", default_value_t = DEFAULT_SERVE_PORT)]
     client_port:        i32,
+
+    // This field lists the WebSocket subprotocols the server is willing
+    // to negotiate, in order of preference.
+    #[arg(long = "ws_subprotocols", value_delimiter = ',')]
+    ws_subprotocols:    Vec<String>,
+
+    // This field seeds the server's random number generator so that
+    // generated data can be reproduced between runs.
+    #[arg(long = "seed")]
+    seed:               Option<u64>,
+
+    // This field sets the probability that a generated message omits
+    // its optional geo_tags and thread_id fields.
+    #[arg(long = "sparse_fields", default_value_t = 0.0, value_parser = parse_ratio)]
+    sparse_fields:      f64,
+
+    // When this flag is set, the server runs its startup validation,
+    // reports the result and exits without serving any requests.
+    #[arg(long = "dry_run")]
+    dry_run:            bool,
+
+    // This field sets the classification of the generated data.  Search
+    // requests from users cleared below this level are refused.
+    #[arg(long = "classification", default_value_t = Classification::default())]
+    classification:     Classification,
+
+    // This field names this server instance in the x-mock-instance
+    // header of every response.  It defaults to the host name.
+    #[arg(long = "instance_name", default_value_t = default_instance_name(),
+        value_parser = parse_header_value)]
+    instance_name:      String,
+
+    // This field sets the upper bound of a random delay added to each
+    // response, so that concurrent requests can complete out of order.
+    #[arg(long = "shuffle_latency_ms", default_value_t = 0)]
+    shuffle_latency_ms: u64,
+
+    // This field controls whether Nagle's algorithm is disabled on
+    // accepted connections, so that small frames are sent immediately.
+    #[arg(long = "tcp_nodelay", default_value_t = true, action = clap::ArgAction::Set)]
+    tcp_nodelay:        bool,
+
+    // This field enables TCP keepalive probes on accepted connections
+    // after the given number of idle seconds.
+    #[arg(long = "tcp_keepalive_secs")]
+    tcp_keepalive_secs: Option<u64>,
+
+    // This field sets how long the response to a request carrying an
+    // Idempotency-Key header is remembered.
+    #[arg(long = "idempotency_ttl_secs", default_value_t = DEFAULT_IDEMPOTENCY_TTL_SECS)]
+    idempotency_ttl_secs: u64,
+
+    // This field caps the number of messages returned by the get
+    // messages route, including any count the client asks for.
+    // Truncated responses are marked with a header.
+    #[arg(long = "max_get_messages")]
+    max_get_messages:   Option<usize>,
+
+    // When set, outbound chat messages longer than this many bytes are
+    // sent as a series of fragments for the client to reassemble.
+    #[arg(long = "ws_fragment_bytes")]
+    ws_fragment_bytes:  Option<NonZeroUsize>,
+
+    // This field sets the probability that a generated WebSocket message
+    // is silently dropped, to simulate a lossy link.
+    #[arg(long = "ws_loss_rate", default_value_t = 0.0, value_parser = parse_ratio)]
+    ws_loss_rate:       f64,
+
+    // This field sets the probability that a generated WebSocket message
+    // is sent twice in a row, to simulate at-least-once delivery.
+    #[arg(long = "ws_dup_rate", default_value_t = 0.0, value_parser = parse_ratio)]
+    ws_dup_rate:        f64,
+
+    // These fields limit the size of WebSocket frames and messages.  The
+    // same limits are applied to the messages we send.
+    #[arg(long = "ws_max_frame_bytes")]
+    ws_max_frame_bytes:     Option<usize>,
+
+    #[arg(long = "ws_max_message_bytes")]
+    ws_max_message_bytes:   Option<usize>,
+
+    // This field sets the number of seconds between the heartbeat frames
+    // sent to WebSocket clients that ask for them.
+    #[arg(long = "heartbeat_interval_secs", default_value_t = DEFAULT_HEARTBEAT_INTERVAL_SECS,
+        value_parser = clap::value_parser!(u64).range(1..))]
+    heartbeat_interval_secs: u64,
+
+    // This field lists the nicknames that posted messages may not use.
+    // They are compared without regard to case.
+    #[arg(long = "reserved_nicknames", value_delimiter = ',')]
+    reserved_nicknames: Vec<String>,
+
+    // This field sets the largest number of seconds by which a generated
+    // message's timestamp may be shifted into the past or future.
+    #[arg(long = "clock_skew_secs", default_value_t = 0)]
+    clock_skew_secs:    u32,
+
+    // These fields set the markup placed around matched keywords when a
+    // search request asks for its results to be highlighted.
+    #[arg(long = "highlight_prefix", default_value_t = String::from(DEFAULT_HIGHLIGHT_PREFIX))]
+    highlight_prefix:   String,
+
+    #[arg(long = "highlight_suffix", default_value_t = String::from(DEFAULT_HIGHLIGHT_SUFFIX))]
+    highlight_suffix:   String,
+
+    // This field sets the status of a successful post, either 204 with no
+    // body or 201 with the created message.
+    #[arg(long = "post_success_status", default_value_t = StatusCode::NO_CONTENT.as_u16(),
+        value_parser = parse_post_success_status)]
+    post_success_status: u16,
+
+    // This field sets the number of requests each data route answers with
+    // 503 Service Unavailable after startup, before behaving normally.
+    #[arg(long = "warmup_failures", default_value_t = 0)]
+    warmup_failures:    u64,
+
+    // This field sets the longest message text, in bytes, that can be
+    // posted.
+    #[arg(long = "max_message_len", default_value_t = DEFAULT_MAX_MESSAGE_LEN)]
+    max_message_len:    usize,
+
+    // When this flag is set, WebSocket streams cycle through a fixed
+    // script of senders, rooms and texts instead of random messages.  A
+    // room's subscribers share its place in the script, so only the
+    // first to connect sees it from the start.
+    #[arg(long = "demo_mode")]
+    demo_mode:          bool,
+
+    // This field sets the key used to sign search cursors.  Without it, a
+    // random key is chosen at startup.  It is kept out of the logged
+    // configuration.
+    #[arg(long = "cursor_secret")]
+    #[serde(skip_serializing)]
+    cursor_secret:      Option<String>,
+
+    // This field sets the location type of generated geo-tags: point,
+    // polygon, or mixed to alternate between the two.
+    #[arg(long = "geo_location_type", default_value_t = GeoLocationType::default())]
+    geo_location_type:  GeoLocationType,
+
+    // When this flag is set, generated messages take their senders and
+    // texts from a pool of CJK, right-to-left and emoji strings.
+    #[arg(long = "unicode_sample")]
+    unicode_sample:     bool,
+
+    // This field sets the number of milliseconds between the messages
+    // generated on each WebSocket stream.  It can be changed while the
+    // server runs through the admin endpoint.
+    #[arg(long = "ws_interval_ms", default_value_t = DEFAULT_WS_INTERVAL_MS,
+        value_parser = clap::value_parser!(u64).range(1..))]
+    ws_interval_ms:     u64,
+
+    // When this flag is set, the admin endpoints that change the server's
+    // behavior while it runs are enabled.
+    #[arg(long = "enable_admin")]
+    enable_admin:       bool,
+
+    // This field sets the number of messages generated on each WebSocket
+    // push.  Above 1, they are sent together in one frame as a JSON array,
+    // and so are posted and replayed messages.
+    #[arg(long = "ws_batch_size", default_value_t = 1,
+        value_parser = clap::value_parser!(u64).range(1..))]
+    ws_batch_size:      u64,
+
+    // When this field is set, the server also listens on a Unix domain
+    // socket at this path, which is removed again on shutdown.
+    #[arg(long = "uds_path")]
+    uds_path:           Option<std::path::PathBuf>,
+
+    // When this flag is set, WebSocket upgrades are refused unless they
+    // carry the API key in an api-key header or a token query parameter.
+    #[arg(long = "require_ws_auth")]
+    require_ws_auth:    bool,
+
+    // This field names a JSON file of {"sender","text"} templates that
+    // generated messages draw their content from.  The file is reloaded
+    // whenever it changes.
+    #[arg(long = "template_file")]
+    template_file:      Option<std::path::PathBuf>,
+
+    // This field sets the most regions a geo-tag on an injected message
+    // may have.
+    #[arg(long = "max_regions_per_geotag", default_value_t = MAX_REGIONS)]
+    max_regions_per_geotag: usize,
+
+    // This field lists the classifications generated messages are drawn
+    // from, such as UNCLASSIFIED,CONFIDENTIAL,SECRET.  When it is empty,
+    // every message has the configured classification.
+    #[arg(long = "classification_mix", value_delimiter = ',')]
+    classification_mix: Vec<Classification>,
+
+    // This field sets how a search whose query has no keywords is
+    // handled: reject it with a 400, or match every message.
+    #[arg(long = "empty_query_behavior", default_value_t = EmptyQueryBehavior::default())]
+    empty_query_behavior:   EmptyQueryBehavior,
+
+    // This field describes a recurring latency spike as <every>/<add>/<for>.
+    // For example, 30s/500ms/2s delays every response by 500ms for the
+    // last 2s of every 30s.
+    #[arg(long = "latency_spike", value_parser = middleware::LatencySpike::parse)]
+    latency_spike:      Option<middleware::LatencySpike>,
+
+    // This field limits how many TCP connections are accepted each second.
+    // Connections beyond the rate wait in the listen backlog, as they
+    // would on a busy server.  By default, accepting is unthrottled.
+    #[arg(long = "accept_rate_per_sec",
+        value_parser = clap::value_parser!(u64).range(1..))]
+    accept_rate_per_sec: Option<u64>,
+
+    // When this field is set, every text and binary frame received from a
+    // WebSocket client is appended to the file as a line of JSON.
+    #[arg(long = "ws_capture_file")]
+    ws_capture_file:    Option<std::path::PathBuf>,
+
+    // When this flag is set, clients may speak HTTP/2 with prior knowledge
+    // (h2c) as well as HTTP/1.1.  The WebSocket routes need HTTP/1.1.
+    #[arg(long = "http2")]
+    http2:              bool,
+
+    // When this flag is set, generated messages carry a senderMeta object
+    // with an avatar URL and colour that are the same for every message
+    // from a sender.
+    #[arg(long = "sender_meta")]
+    sender_meta:        bool,
+
+    // This field sets the probability that a generated message is given
+    // a malformed classification marking, such as UNCLAS or SECRET//BOGUS.
+    #[arg(long = "invalid_classification_rate", default_value_t = 0.0, value_parser = parse_ratio)]
+    invalid_classification_rate: f64,
+
+    // This field limits the number of HTTP requests handled at once.
+    // Requests beyond the limit are refused with 503 Service Unavailable.
+    #[arg(long = "max_inflight", value_parser = clap::value_parser!(u64).range(1..))]
+    max_inflight:       Option<u64>,
+
+    // This field sets the range, such as 50-100, from which each WebSocket
+    // connection draws the number of messages it is sent before the server
+    // closes it with 1012 (service restart).  A resume token is sent just
+    // before the close, so that the client can continue the stream.
+    #[arg(long = "ws_recycle_after", value_parser = resume::RecycleRange::parse)]
+    ws_recycle_after:   Option<resume::RecycleRange>,
+
+    // This field limits how many frames a WebSocket client may send each
+    // second.  The first frame over the limit is answered with an error
+    // frame, and a client sending twice the limit is disconnected with
+    // 1008 (policy violation).  By default, clients are unlimited.
+    #[arg(long = "ws_inbound_rate",
+        value_parser = clap::value_parser!(u32).range(1..))]
+    ws_inbound_rate:    Option<u32>,
+
+    // When this flag is set, the server checks that its main structures
+    // survive a round trip through JSON before serving, and exits if any
+    // doesn't.
+    #[arg(long = "self_test")]
+    self_test:          bool,
+
+    // When this flag is set, the keys of every JSON object in an HTTP
+    // response are sorted, rather than being in declaration order, so
+    // that responses can be compared byte for byte.
+    #[arg(long = "canonical_json")]
+    canonical_json:     bool,
+
+    // This field sets how long a posted message stays out of the get and
+    // search responses, as a write would in an eventually consistent
+    // store.  WebSocket subscribers still receive it straight away.
+    #[arg(long = "write_visibility_delay_ms", default_value_t = 0)]
+    write_visibility_delay_ms: u64,
+
+    // This field sets a prefix, such as /mock, under which every route
+    // is served, for running behind a gateway that mounts the server
+    // there.
+    #[arg(long = "base_path", value_parser = parse_base_path)]
+    base_path:          Option<String>,
+
+    // When this flag is set along with a base path, the test and state
+    // routes are also served without the prefix, so that health checks
+    // needn't know it.
+    #[arg(long = "health_unprefixed")]
+    health_unprefixed:  bool,
+
+    // This field names a PEM file holding the RSA private key that access
+    // tokens are signed with, so that the published public key stays the
+    // same between runs.  Without it, a key is generated at startup.
+    #[arg(long = "token_key_file")]
+    token_key_file:     Option<std::path::PathBuf>,
 }
 
 impl Args {
     pub fn to_json(&self) -> String {
         serde_json::to_string(self).unwrap()
     }
+
+    /// This method returns the route in ROUTES that the given matched path
+    /// was registered for, by removing any base path from it.
+    pub fn route_template<'a>(&self, matched_path: &'a str) -> &'a str {
+        self.base_path.as_deref()
+            .and_then(|base_path| matched_path.strip_prefix(base_path))
+            .unwrap_or(matched_path)
+    }
+
+    /// This method returns the highest classification a generated message
+    /// can have, taking the classification mix into account.
+    pub fn highest_classification(&self) -> Classification {
+        self.classification_mix.iter()
+            .copied()
+            .fold(self.classification, Classification::max)
+    }
+
+    /// This method returns the largest text frame we may send.  We never
+    /// split a frame across several, so it has to fit within both limits.
+    pub fn ws_frame_limit(&self) -> Option<usize> {
+        match (self.ws_max_frame_bytes, self.ws_max_message_bytes) {
+            (Some(frame), Some(message)) => Some(frame.min(message)),
+            (frame, message) => frame.or(message),
+        }
+    }
+
+    /// This method constructs the address string we're going to
+    /// serve from.
+    pub fn serve_address(&self) -> String {
+        format!("{}:{}", self.client_serve_ip, self.client_port)
+    }
+}
+
+/// This enum lists the ways a search with an empty query can be handled.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize)]
+#[derive(strum_macros::EnumString, strum_macros::Display)]
+enum EmptyQueryBehavior {
+    #[default]
+    #[strum(serialize = "reject")]
+    #[serde(rename = "reject")]
+    Reject,
+
+    #[strum(serialize = "all")]
+    #[serde(rename = "all")]
+    All,
+}
+
+/// This function performs the startup checks that can fail because of a
+/// bad configuration, returning the first failure encountered.
+async fn validate_config(args: &Args) -> Result<(), anyhow::Error> {
+    // Make sure the address is free by binding to it and immediately
+    // releasing it again.
+    let listener = tokio::net::TcpListener::bind(args.serve_address()).await
+        .with_context(|| format!("Unable to bind to {}", args.serve_address()))?;
+    drop(listener);
+
+    // Likewise for the Unix socket, removing the socket file afterwards.
+    #[cfg(unix)]
+    if let Some(path) = &args.uds_path {
+        drop(bind_unix_socket(path)?);
+
+        std::fs::remove_file(path)
+            .with_context(|| format!("Unable to remove the Unix socket {}", path.display()))?;
+    }
+
+    if let Some(path) = &args.template_file {
+        templates::TemplateStore::load(path)?;
+    }
+
+    if let Some(path) = &args.token_key_file {
+        token::TokenIssuer::new(Some(path.as_path()))?;
+    }
+
+    // Make sure the capture file can be written, without leaving behind
+    // one that didn't exist before.
+    if let Some(path) = &args.ws_capture_file {
+        let existed = path.exists();
+        capture::open_capture_file(path)?;
+
+        if !existed {
+            std::fs::remove_file(path)
+                .with_context(|| format!("Unable to remove the capture file {}", path.display()))?;
+        }
+    }
+
+    Ok(())
+} // end validate_config
+
+/// This function returns the host name, which identifies the server
+/// instance unless a name is given.
+fn default_instance_name() -> String {
+    gethostname::gethostname().to_string_lossy().into_owned()
+}
+
+/// This function parses an argument that will be sent as a header value.
+fn parse_header_value(value: &str) -> Result<String, String> {
+    match HeaderValue::from_str(value) {
+        Ok(_) => Ok(String::from(value)),
+        Err(e) => Err(format!("{} is not a valid header value: {}", value, e)),
+    }
+}
+
+/// This function parses the prefix routes are served under, which has to
+/// start with a slash.  Any trailing slash is dropped.
+fn parse_base_path(value: &str) -> Result<String, String> {
+    let trimmed = value.trim_end_matches('/');
+
+    if !trimmed.starts_with('/') {
+        return Err(format!("{} is not a base path, such as /mock", value));
+    }
+
+    Ok(String::from(trimmed))
+}
+
+/// This function parses the status returned by a successful post.
+fn parse_post_success_status(value: &str) -> Result<u16, String> {
+    match value.parse::<u16>() {
+        Ok(201) => Ok(201),
+        Ok(204) => Ok(204),
+        _ => Err(format!("{} is not 201 or 204", value)),
+    }
+}
+
+/// This function parses a probability argument, rejecting values
+/// outside of the range 0.0 to 1.0.
+fn parse_ratio(value: &str) -> Result<f64, String> {
+    let ratio: f64 = value.parse()
+        .map_err(|e| format!("{} is not a number: {}", value, e))?;
+
+    if (0.0..=1.0).contains(&ratio) {
+        Ok(ratio)
+    } else {
+        Err(format!("{} is not between 0.0 and 1.0", ratio))
+    }
 }
 
-async fn test() {
+// The status and body most of our handlers respond with.
+type HandlerResponse = (StatusCode, String);
+
+// The response to a request with an idempotency key.  The first request
+// with the key fills it in, and any retries made meanwhile wait for it.
+type IdempotentResponse = Arc<tokio::sync::OnceCell<HandlerResponse>>;
+
+/*
+ * This struct holds the state shared between all of the request handlers.
+ */
+#[derive(Clone)]
+struct ServerState {
+    args:   Arc<Args>,
+
+    // All generated data draws from this generator so that a fixed
+    // seed reproduces the same output.
+    rng:    Arc<Mutex<StdRng>>,
+
+    // The time at which the server started, for reporting its uptime.
+    started:    Instant,
+
+    // The number of requests each data route has received, so that the
+    // first few can be failed while the server warms up.
+    warmup_counts:  Arc<HashMap<&'static str, AtomicU64>>,
+
+    // This signs the search cursors handed to clients.
+    cursor_signer:  Arc<cursor::CursorSigner>,
+
+    // This signs the access tokens issued by the token route.
+    token_issuer:   Arc<token::TokenIssuer>,
+
+    // The number of chat messages generated, and of open WebSocket
+    // connections, for reporting the server's state.
+    messages_generated: Arc<AtomicU64>,
+    ws_connections:     Arc<AtomicU64>,
+
+    // The number of milliseconds between generated WebSocket messages,
+    // starting from --ws_interval_ms.
+    ws_interval_ms:     Arc<AtomicU64>,
+
+    // Posted messages are kept here, after the seeded messages, each with
+    // the time it becomes visible to reads.  They are published on the
+    // broadcast channel for the WebSocket subscribers straight away.
+    store:      Arc<Mutex<Vec<(Instant, ChatMessageSchema)>>>,
+    broadcast:  broadcast::Sender<ChatMessageSchema>,
+
+    // The seeded messages are generated on first use and then kept, so
+    // that repeated requests see the same data.
+    seeded:     Arc<OnceLock<Vec<ChatMessageSchema>>>,
+
+    // The templates loaded from the template file, if one was given.
+    templates:  Option<Arc<templates::TemplateStore>>,
+
+    // The writer of the WebSocket capture file, if one was given.
+    capture:    Option<capture::CaptureWriter>,
+
+    // The most recent log events, for the logs route.
+    logs:       logs::LogBuffer,
+
+    // The feeds of generated messages for the rooms being streamed.
+    rooms:      Arc<rooms::RoomFeeds>,
+
+    // The recently sent messages of each WebSocket stream, for resuming.
+    streams:    Arc<Mutex<resume::StreamBuffers>>,
+
+    // This map holds the responses to requests with idempotency keys,
+    // along with when each one was stored.
+    idempotency_cache:  Arc<Mutex<HashMap<String, (Instant, IdempotentResponse)>>>,
+}
+
+impl ServerState {
+    /// This function builds the server's state, failing if the template
+    /// or capture file can't be opened.
+    pub fn new(args: Args, logs: logs::LogBuffer) -> Result<ServerState, anyhow::Error> {
+        let rng = match args.seed {
+            Some(seed) => StdRng::seed_from_u64(seed),
+            None => StdRng::from_entropy(),
+        };
+
+        // Without a configured secret, cursors are only valid for this run.
+        // The key doesn't come from the seeded generator, so that it
+        // doesn't disturb the generated data.
+        let cursor_key = match &args.cursor_secret {
+            Some(secret) => secret.as_bytes().to_vec(),
+            None => rand::random::<[u8; 32]>().to_vec(),
+        };
+
+        let ws_interval_ms = args.ws_interval_ms;
+
+        let templates = args.template_file.as_deref()
+            .map(|path| templates::TemplateStore::load(path).map(Arc::new))
+            .transpose()?;
+
+        let capture = args.ws_capture_file.as_deref()
+            .map(capture::CaptureWriter::open)
+            .transpose()?;
+
+        let token_issuer = token::TokenIssuer::new(args.token_key_file.as_deref())?;
+
+        Ok(ServerState {
+            args:   Arc::new(args),
+            rng:    Arc::new(Mutex::new(rng)),
+            started:    Instant::now(),
+            warmup_counts:  Arc::new(ROUTES.iter()
+                .filter(|route| route.data)
+                .map(|route| (route.path, AtomicU64::new(0)))
+                .collect()),
+            cursor_signer:  Arc::new(cursor::CursorSigner::new(cursor_key)),
+            token_issuer:   Arc::new(token_issuer),
+            messages_generated: Arc::new(AtomicU64::new(0)),
+            ws_connections:     Arc::new(AtomicU64::new(0)),
+            ws_interval_ms:     Arc::new(AtomicU64::new(ws_interval_ms)),
+            store:      Arc::new(Mutex::new(Vec::new())),
+            broadcast:  broadcast::channel(BROADCAST_CAPACITY).0,
+            seeded:     Arc::new(OnceLock::new()),
+            templates,
+            capture,
+            rooms:      Arc::new(rooms::RoomFeeds::default()),
+            logs,
+            streams:    Arc::new(Mutex::new(resume::StreamBuffers::default())),
+            idempotency_cache:  Arc::new(Mutex::new(HashMap::new())),
+        })
+    }
+
+    /// This method stores a posted message and delivers it to any
+    /// subscribers of its room.
+    pub fn publish(&self, message: ChatMessageSchema) {
+        let visible_at = Instant::now()
+            + Duration::from_millis(self.args.write_visibility_delay_ms);
+        self.store.lock().unwrap().push((visible_at, message.clone()));
+
+        // It is not an error for nobody to be listening.
+        let _ = self.broadcast.send(message);
+    }
+
+    /// This method counts a request to the given route, returning true if
+    /// it is one of the requests to fail while the server warms up.
+    pub fn is_warming_up(&self, route: &str) -> bool {
+        match self.warmup_counts.get(route) {
+            Some(count) => count.fetch_add(1, Ordering::Relaxed) < self.args.warmup_failures,
+            None => false,
+        }
+    }
+
+    /// This method returns the current interval between generated
+    /// WebSocket messages.
+    pub fn ws_interval(&self) -> Duration {
+        Duration::from_millis(self.ws_interval_ms.load(Ordering::Relaxed))
+    }
+
+    /// This method returns the seeded messages, generating them the first
+    /// time they're needed.
+    pub fn seeded_messages(&self) -> &[ChatMessageSchema] {
+        self.seeded.get_or_init(|| build_seeded_messages(self))
+    }
+
+    /// This method returns a copy of the posted messages that have become
+    /// visible.
+    pub fn stored_messages(&self) -> Vec<ChatMessageSchema> {
+        let now = Instant::now();
+
+        self.store.lock().unwrap().iter()
+            .filter(|(visible_at, _)| *visible_at <= now)
+            .map(|(_, message)| message.clone())
+            .collect()
+    }
+
+    /// This method returns the response for the given idempotency key,
+    /// and whether an unexpired request with the key came first.  Looking
+    /// the key up and reserving it happen under one lock, so concurrent
+    /// requests with the same key always share a single response.
+    pub fn idempotent_response(&self, key: String) -> (IdempotentResponse, bool) {
+        let ttl = Duration::from_secs(self.args.idempotency_ttl_secs);
+        let mut cache = self.idempotency_cache.lock().unwrap();
+
+        // Drop expired entries so the cache doesn't grow without bound.
+        cache.retain(|_, (stored, _)| stored.elapsed() < ttl);
+
+        match cache.entry(key) {
+            Entry::Occupied(entry) => (entry.get().1.clone(), true),
+            Entry::Vacant(entry) => (entry.insert((Instant::now(), Arc::default())).1.clone(), false),
+        }
+    }
+
+    /// This method draws a random value from the server's generator.
+    pub fn random<T>(&self) -> T
+    where
+        Standard: Distribution<T>
+    {
+        self.rng.lock().unwrap().gen()
+    }
+
+    /// This method draws a random value within the given range from the
+    /// server's generator.
+    pub fn random_range<T, R>(&self, range: R) -> T
+    where
+        T: SampleUniform,
+        R: SampleRange<T>,
+    {
+        self.rng.lock().unwrap().gen_range(range)
+    }
+
+    /// This method returns true with the given probability.
+    pub fn random_bool(&self, probability: f64) -> bool {
+        self.rng.lock().unwrap().gen_bool(probability)
+    }
+} // end ServerState
+
+/// This function periodically logs that the server is still running.  It
+/// is spawned once at startup.
+async fn log_liveness() {
+    let mut interval = tokio::time::interval(
+        Duration::from_secs(SECONDS_BETWEEN_LIVENESS_LOGS));
 
     loop {
-        
+        interval.tick().await;
         event!(Level::DEBUG, "Thread {}: spinning", thread_id::get());
-        
-        thread::sleep(time::Duration::from_secs(10));
     }
+} // end log_liveness
+
+async fn handle_test(
+    State(state): State<ServerState>,
+) -> (StatusCode, String) {
+    event!(Level::DEBUG, "Received the Test Request");
+
+    let body = serde_json::json!({
+        "taskId":       tokio::task::try_id().map(|id| id.to_string()),
+        "threadId":     thread_id::get(),
+        "uptimeSecs":   state.started.elapsed().as_secs(),
+        "config":       &*state.args,
+    });
 
+    (StatusCode::OK, body.to_string())
+} // end handle_test
+
+async fn handle_server_state(
+    State(state): State<ServerState>,
+) -> (StatusCode, String) {
+    event!(Level::DEBUG, "Received the Server State Request");
+
+    let body = serde_json::json!({
+        "seed":                 state.args.seed,
+        "messages_generated":   state.messages_generated.load(Ordering::Relaxed),
+        "ws_connections":       state.ws_connections.load(Ordering::Relaxed),
+        "uptime_secs":          state.started.elapsed().as_secs(),
+    });
+
+    (StatusCode::OK, body.to_string())
+} // end handle_server_state
+
+/// This function reflects the request body back to the client, so that it
+/// can compare what it sent with what it meant to send.  The body is not
+/// validated, and any bytes that aren't UTF-8 are replaced.
+async fn handle_echo(
+    headers:    HeaderMap,
+    body:       axum::body::Bytes,
+) -> (StatusCode, String) {
+    event!(Level::DEBUG, "Received the Echo Request");
+
+    let content_type = headers.get(CONTENT_TYPE)
+        .map(|value| String::from_utf8_lossy(value.as_bytes()).into_owned());
+
+    let body = serde_json::json!({
+        "bytes":        body.len(),
+        "content_type": content_type,
+        "body":         String::from_utf8_lossy(&body),
+    });
+
+    (StatusCode::OK, body.to_string())
+} // end handle_echo
+
+/// This function refuses requests to the admin routes unless the server
+/// was started with --enable_admin.
+fn require_admin(state: &ServerState) -> Result<(), (StatusCode, String)> {
+    if state.args.enable_admin {
+        return Ok(());
+    }
+
+    let body = messages::ErrorCode403 {
+        message: String::from("Admin endpoints are disabled; start the server with --enable_admin."),
+        ..Default::default()
+    };
+
+    Err((StatusCode::FORBIDDEN, body.to_string()))
+}
+
+/// This function changes the interval between generated WebSocket
+/// messages, for open connections as well as new ones.
+async fn handle_set_ws_interval(
+    State(state): State<ServerState>,
+    payload:    String,
+) -> (StatusCode, String) {
+    event!(Level::DEBUG, "Received the WebSocket Interval Request: {}", payload);
+
+    if let Err(response) = require_admin(&state) {
+        return response;
+    }
+
+    // The body is {"interval_ms": <ms>}.  Only the field's value is
+    // reported back when it isn't valid, not the whole body.
+    let request = serde_json::from_str::<serde_json::Value>(&payload).ok();
+    let field = request.as_ref().and_then(|request| request.get("interval_ms"));
+
+    let interval_ms = match field.and_then(serde_json::Value::as_u64) {
+        Some(interval_ms) if interval_ms > 0 => interval_ms,
+        _ => {
+            return bad_request(messages::ErrorCode400 {
+                field_errors: vec![messages::FieldErrorSchema {
+                    field_name:     String::from("interval_ms"),
+                    message:        String::from("Interval must be a positive number of milliseconds"),
+                    message_code:   String::from("IntervalIsInvalid"),
+                    rejected_value: field.map(|value| value.to_string()).unwrap_or_default(),
+                    ..Default::default()
+                }],
+                message: String::from("The request contained 1 or more field validation errors."),
+                ..Default::default()
+            });
+        }
+    };
+
+    state.ws_interval_ms.store(interval_ms, Ordering::Relaxed);
+    event!(Level::INFO, "The WebSocket push interval is now {}ms", interval_ms);
+
+    (StatusCode::OK, serde_json::json!({ "interval_ms": interval_ms }).to_string())
+} // end handle_set_ws_interval
+
+/// This struct describes the query parameters accepted by the busy route.
+#[derive(Deserialize)]
+struct BusyQuery {
+    // The number of milliseconds to spin for.
+    ms:         u64,
+
+    // When set, the work runs on the blocking thread pool rather than on
+    // the runtime's worker thread.
+    #[serde(default)]
+    offload:    bool,
 }
 
+/// This function keeps the current thread busy for the given duration,
+/// without ever yielding it.
+fn spin_for(duration: Duration) {
+    let deadline = Instant::now() + duration;
+
+    while Instant::now() < deadline {
+        std::hint::spin_loop();
+    }
+}
+
+/// This function deliberately does CPU-bound work inside the handler,
+/// starving the other tasks on its worker thread, so that the effect can
+/// be seen and compared with offloading the work.
+async fn handle_busy(
+    Query(query): Query<BusyQuery>,
+) -> (StatusCode, String) {
+    event!(Level::DEBUG, "Received the Busy Request for {}ms", query.ms);
+
+    let ms = query.ms.min(MAX_BUSY_MS);
+    let duration = Duration::from_millis(ms);
+
+    if query.offload {
+        if let Err(e) = tokio::task::spawn_blocking(move || spin_for(duration)).await {
+            event!(Level::ERROR, "Error - the offloaded busy work failed: {}", e);
+            return (StatusCode::INTERNAL_SERVER_ERROR, e.to_string());
+        }
+    } else {
+        spin_for(duration);
+    }
+
+    let body = serde_json::json!({
+        "ms":           ms,
+        "offloaded":    query.offload,
+    });
+
+    (StatusCode::OK, body.to_string())
+} // end handle_busy
+
+/// This function checks a message injected into the store against the
+/// limits the real API enforces.
+fn validate_injected_message(
+    state:      &ServerState,
+    message:    &ChatMessageSchema,
+) -> Result<(), messages::ErrorCode400> {
+    let max_regions = state.args.max_regions_per_geotag;
+
+    if let Some(geo_tag) = message.geo_tags.iter().flatten()
+        .find(|geo_tag| geo_tag.regions.len() > max_regions) {
+        return Err(messages::ErrorCode400 {
+            field_errors: vec![messages::FieldErrorSchema {
+                field_name:     String::from("regions"),
+                message:        format!("A geo-tag may have at most {} regions", max_regions),
+                message_code:   String::from("TooManyRegions"),
+                rejected_value: geo_tag.regions.len().to_string(),
+                ..Default::default()
+            }],
+            message: String::from("The request contained 1 or more field validation errors."),
+            ..Default::default()
+        });
+    }
+
+    Ok(())
+} // end validate_injected_message
+
+/// This function stores a complete chat message, as given, and delivers
+/// it to the WebSocket subscribers of its room.  Unlike a posted message,
+/// every field is under the client's control.
+async fn handle_inject_message(
+    State(state): State<ServerState>,
+    payload:    String,
+) -> (StatusCode, String) {
+    event!(Level::DEBUG, "Received the Inject Message Request: {}", payload);
+
+    let message = match ChatMessageSchema::try_from_json(payload) {
+        Ok(message) => message,
+        Err(e) => return bad_request(e.into()),
+    };
+
+    if let Err(body) = validate_injected_message(&state, &message) {
+        return bad_request(body);
+    }
+
+    let body = message.try_to_json().unwrap();
+    state.publish(message);
+
+    (StatusCode::CREATED, body)
+} // end handle_inject_message
+
+/// This struct describes the query parameters accepted by the slow body
+/// route.
+#[derive(Deserialize)]
+struct SlowBodyQuery {
+    #[serde(default = "default_slow_body_chunk_delay_ms")]
+    chunk_delay_ms: u64,
+
+    #[serde(default = "default_slow_body_chunk_bytes")]
+    chunk_bytes:    usize,
+}
+
+fn default_slow_body_chunk_delay_ms() -> u64 {
+    DEFAULT_SLOW_BODY_CHUNK_DELAY_MS
+}
+
+fn default_slow_body_chunk_bytes() -> usize {
+    DEFAULT_SLOW_BODY_CHUNK_BYTES
+}
+
+/// This function returns the same body as the get messages route, but
+/// streams it in small chunks with a pause between each, so that clients
+/// can test how they handle a body that stalls part of the way through.
+async fn handle_slow_body(
+    State(state): State<ServerState>,
+    Query(query): Query<SlowBodyQuery>,
+) -> Response {
+    event!(Level::DEBUG, "Received the Slow Body Request");
+
+    let mut response = build_get_messages_response(&state);
+
+    if let Some(highest) = highest_classification(&response.messages) {
+        response.classification = highest.to_string();
+    }
+
+    let body = response.try_to_json().unwrap();
+    let delay = Duration::from_millis(query.chunk_delay_ms);
+
+    let chunks: Vec<axum::body::Bytes> = body.as_bytes()
+        .chunks(query.chunk_bytes.max(1))
+        .map(axum::body::Bytes::copy_from_slice)
+        .collect();
+
+    // The first chunk goes straight away, and each after it waits.
+    let stream = futures_util::stream::iter(chunks.into_iter().enumerate())
+        .then(move |(index, chunk)| async move {
+            if index > 0 {
+                tokio::time::sleep(delay).await;
+            }
+
+            Ok::<_, std::convert::Infallible>(chunk)
+        });
+
+    (
+        StatusCode::OK,
+        [(CONTENT_TYPE, "application/json")],
+        axum::body::Body::from_stream(stream),
+    ).into_response()
+} // end handle_slow_body
+
+async fn handle_list_routes() -> (StatusCode, String) {
+    event!(Level::DEBUG, "Received the List Routes Request");
+
+    let routes: Vec<serde_json::Value> = ROUTES.iter()
+        .map(|route| serde_json::json!({
+            "method":   route.method.as_str(),
+            "path":     route.path,
+        }))
+        .collect();
+
+    (StatusCode::OK, serde_json::to_string(&routes).unwrap())
+} // end handle_list_routes
+
+/// This function describes the routes in ROUTES as an OpenAPI document.
+/// It lists each operation without its schemas, which is enough to
+/// browse the server and try the routes out.
+async fn handle_openapi(
+    State(state): State<ServerState>,
+) -> (StatusCode, [(HeaderName, &'static str); 1], String) {
+    event!(Level::DEBUG, "Received the OpenAPI Request");
+
+    let mut paths = serde_json::Map::new();
+
+    for route in ROUTES {
+        // OpenAPI writes path parameters in braces rather than after a
+        // colon.
+        let path = route.path.split('/')
+            .map(|segment| match segment.strip_prefix(':') {
+                Some(name) => format!("{{{}}}", name),
+                None => String::from(segment),
+            })
+            .collect::<Vec<String>>()
+            .join("/");
+
+        let operations = paths.entry(path)
+            .or_insert_with(|| serde_json::json!({}));
+
+        operations[route.method.as_str().to_lowercase()] = serde_json::json!({
+            "summary":      route.summary,
+            "tags":         [if route.data { "ChatSurfer" } else { "Test" }],
+            "responses":    { "200": { "description": "OK" } },
+        });
+    }
+
+    let body = serde_json::json!({
+        "openapi":  "3.0.3",
+        "info": {
+            "title":    "WebSocket-EchoServer",
+            "version":  env!("CARGO_PKG_VERSION"),
+        },
+        "servers":  [{ "url": state.args.base_path.as_deref().unwrap_or("/") }],
+        "paths":    paths,
+    });
+
+    (StatusCode::OK, [(CONTENT_TYPE, "application/json")], body.to_string())
+} // end handle_openapi
+
+/// This struct describes the query parameters accepted by the logs route.
+#[derive(Deserialize)]
+struct LogsQuery {
+    #[serde(default = "default_logs_count")]
+    n:  usize,
+}
+
+fn default_logs_count() -> usize {
+    DEFAULT_LOGS_COUNT
+}
+
+/// This function returns the most recent log events, oldest first, for
+/// clients that can't see the server's output.
+async fn handle_logs(
+    State(state): State<ServerState>,
+    Query(query): Query<LogsQuery>,
+) -> (StatusCode, String) {
+    if let Err(response) = require_admin(&state) {
+        return response;
+    }
+
+    (StatusCode::OK, serde_json::to_string(&state.logs.recent(query.n)).unwrap())
+} // end handle_logs
+
+/// This function serves a page for browsing the OpenAPI document.  The
+/// page is built into the binary and loads nothing else, so it works
+/// without network access.
+async fn handle_docs() -> Html<&'static str> {
+    event!(Level::DEBUG, "Received the Docs Request");
+
+    Html(include_str!("docs.html"))
+} // end handle_docs
+
+/// This function registers the handler for each wanted entry in ROUTES.
+fn build_routes(wanted: impl Fn(&RouteDescription) -> bool) -> Router<ServerState> {
+    let mut router = Router::new();
+
+    for route in ROUTES.iter().filter(|route| wanted(route)) {
+        let filter = MethodFilter::try_from(route.method.clone()).unwrap();
+
+        let method_router: MethodRouter<ServerState> = match route.path {
+            PUBLIC_KEY_ROUTE        => on(filter, handle_public_key_request),
+            TOKEN_ROUTE             => on(filter, handle_token_request),
+            GET_API_KEY_ROUTE       => on(filter, handle_get_api_key),
+            MESSAGES_ROUTE          => on(filter, handle_get_messages),
+            NEW_MESSAGE_ROUTE       => on(filter, handle_post_chat_message),
+            BULK_MESSAGES_ROUTE     => on(filter, handle_post_bulk_messages),
+            SEARCH_MESSAGES_ROUTE   => on(filter, handle_search_messages),
+            ROOM_CLASSIFICATION_ROUTE => on(filter, handle_room_classification),
+            CLASSIFICATIONS_ROUTE   => on(filter, handle_classifications),
+            WS_SINGLE_ROOM_ROUTE    => on(filter, serve_ws_single_room_upgrade_handler),
+            TEST_ROUTE              => on(filter, handle_test),
+            LIST_ROUTES_ROUTE       => on(filter, handle_list_routes),
+            SERVER_STATE_ROUTE      => on(filter, handle_server_state),
+            ECHO_ROUTE              => on(filter, handle_echo),
+            WS_INTERVAL_ROUTE       => on(filter, handle_set_ws_interval),
+            BUSY_ROUTE              => on(filter, handle_busy),
+            INJECT_ROUTE            => on(filter, handle_inject_message),
+            SLOW_BODY_ROUTE         => on(filter, handle_slow_body),
+            OPENAPI_ROUTE           => on(filter, handle_openapi),
+            DOCS_ROUTE              => on(filter, handle_docs),
+            LOGS_ROUTE              => on(filter, handle_logs),
+            _ => panic!("No handler is defined for the route {}", route.path),
+        };
+
+        router = router.route(route.path, method_router);
+    }
+
+    router
+} // end build_routes
+
+/// This function logs a single line summarizing how this instance was
+/// launched, so that each run leaves an easily found record.  The TCP
+/// address is the one actually bound, so it's passed in once the listener
+/// exists.  The server only speaks plain HTTP, so TLS is always reported
+/// as off and the targets are given as http:// and unix: URLs.
+///
+/// There is no single error rate to report.  Faults are injected by three
+/// independent probabilities, each applying to different traffic: invalid
+/// classifications in generated messages, and WebSocket messages dropped
+/// or duplicated.  Each is logged under its own flag's name.
+fn print_startup_banner(args: &Args, routes: &[&str], local_address: std::net::SocketAddr) {
+    let mut listen = vec!(format!("http://{}", local_address));
+
+    if cfg!(unix) {
+        if let Some(path) = &args.uds_path {
+            listen.push(format!("unix:{}", path.display()));
+        }
+    }
+
+    event!(
+        Level::INFO,
+        version = env!("CARGO_PKG_VERSION"),
+        listen = listen.join(", "),
+        tls = "off",
+        log_level = %LOG_LEVEL,
+        invalid_classification_rate = args.invalid_classification_rate,
+        ws_loss_rate = args.ws_loss_rate,
+        ws_dup_rate = args.ws_dup_rate,
+        instance = args.instance_name,
+        route_count = routes.len(),
+        "WebSocket-EchoServer starting"
+    );
+} // end print_startup_banner
+
+/// This function binds a Unix domain socket at the given path.  A socket
+/// left behind by an earlier run is removed first, but any other kind of
+/// file is left alone.
+#[cfg(unix)]
+fn bind_unix_socket(path: &std::path::Path) -> Result<tokio::net::UnixListener, anyhow::Error> {
+    use std::os::unix::fs::FileTypeExt;
+
+    if std::fs::symlink_metadata(path).is_ok_and(|metadata| metadata.file_type().is_socket()) {
+        std::fs::remove_file(path)
+            .with_context(|| format!("Unable to remove the stale socket {}", path.display()))?;
+    }
+
+    tokio::net::UnixListener::bind(path)
+        .with_context(|| format!("Unable to bind to the Unix socket {}", path.display()))
+} // end bind_unix_socket
+
+/// This function completes when the server is asked to stop, by Ctrl-C
+/// or, on Unix, by SIGTERM.
+async fn shutdown_signal() {
+    #[cfg(unix)]
+    {
+        let mut terminate = tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate())
+            .expect("Unable to listen for SIGTERM");
+
+        tokio::select! {
+            _ = tokio::signal::ctrl_c() => {}
+            _ = terminate.recv() => {}
+        }
+    }
+
+    #[cfg(not(unix))]
+    let _ = tokio::signal::ctrl_c().await;
+} // end shutdown_signal
+
 #[tokio::main]
 //-> Result<(), Box<dyn std::error::Error + Send + Sync>>
 async fn main()  {
-    tracing_subscriber::fmt()
-        .with_max_level(tracing::Level::DEBUG)
+    // Besides writing the log out, keep the latest events for the logs
+    // route.
+    let log_buffer = logs::LogBuffer::default();
+
+    tracing_subscriber::registry()
+        .with(tracing_subscriber::fmt::layer())
+        .with(log_buffer.clone())
+        .with(tracing_subscriber::filter::LevelFilter::from_level(LOG_LEVEL))
         .init();
 
     // Parse the command line arguments and log them.
     let args = Args::parse();
     event!(Level::DEBUG, "{}", args.to_json());
 
-    // Construct the address string we're going to serve from.
-    let serve_address: String = format!("{}:{}", args.client_serve_ip, args.client_port);
-    event!(Level::DEBUG, "Hosting at {}", serve_address);
-
+    if args.dry_run {
+        match validate_config(&args).await {
+            Ok(()) => {
+                event!(Level::INFO, "Dry run complete, the configuration is valid.");
+                return;
+            }
+            Err(e) => {
+                event!(Level::ERROR, "Dry run failed: {:#}", e);
+                std::process::exit(1);
+            }
+        }
+    }
 
-    let test_route = Router::new()
-        .route("/auth/realms/fmv", get(handle_public_key_request))
-        .route(GET_API_KEY_ROUTE, get(handle_get_api_key))
-        .route(MESSAGES_ROUTE, get(handle_get_messages))
-        .route(NEW_MESSAGE_ROUTE, post(handle_post_chat_message))
-        .route(SEARCH_MESSAGES_ROUTE, post(handle_search_messages))
-        .route(WS_SINGLE_ROOM_ROUTE, get(serve_ws_single_room_upgrade_handler))
-        .route("/test", get(test));
+    if args.self_test {
+        match self_test::run() {
+            Ok(()) => event!(Level::INFO, "Self-test passed"),
+            Err(e) => {
+                event!(Level::ERROR, "Self-test failed: {:#}", e);
+                std::process::exit(1);
+            }
+        }
+    }
 
-    
-    let axum_listener = tokio::net::TcpListener::bind(serve_address).await.unwrap();
+    // Construct the address string we're going to serve from.
+    let serve_address: String = args.serve_address();
 
-    match axum::serve(axum_listener, test_route).await {
-        Ok(()) => {
-            event!(Level::DEBUG, "Serving requests...");
+    let state = match ServerState::new(args, log_buffer) {
+        Ok(state) => state,
+        Err(e) => {
+            event!(Level::ERROR, "Unable to start the server: {:#}", e);
+            std::process::exit(1);
         }
+    };
+    let args = state.args.clone();
+
+    // Keep the template file watcher alive for as long as we serve.
+    let _template_watcher = match state.templates.clone().map(templates::watch).transpose() {
+        Ok(watcher) => watcher,
         Err(e) => {
-            event!(Level::ERROR, "Error in the Axum server: {}" , e);
+            event!(Level::ERROR, "Unable to watch the template file: {:#}", e);
+            std::process::exit(1);
+        }
+    };
+
+    let mut test_route = build_routes(|_| true);
+
+    // Serve everything under the base path, if there is one, keeping the
+    // health checks where they were if asked to.
+    if let Some(base_path) = &args.base_path {
+        test_route = Router::new().nest(base_path, test_route);
+
+        if args.health_unprefixed {
+            test_route = test_route.merge(build_routes(|route| HEALTH_ROUTES.contains(&route.path)));
         }
     }
 
+    let mut test_route = test_route
+        .route_layer(tower_http::decompression::RequestDecompressionLayer::new())
+        .route_layer(axum::middleware::from_fn_with_state(state.clone(), middleware::canonical_json))
+        .route_layer(axum::middleware::from_fn_with_state(state.clone(), middleware::warmup_failures))
+        .route_layer(axum::middleware::from_fn_with_state(state.clone(), middleware::shuffle_latency))
+        .route_layer(axum::middleware::from_fn_with_state(state.clone(), middleware::latency_spike))
+        .route_layer(axum::middleware::from_fn(middleware::request_metrics));
+
+    // Refuse requests beyond the in-flight limit, rather than queuing
+    // them, as a backend with bounded capacity would.  The layer is applied
+    // to each route separately, so they share one semaphore to make the
+    // limit server-wide.
+    if let Some(max_inflight) = args.max_inflight {
+        let permits = Arc::new(tokio::sync::Semaphore::new(max_inflight as usize));
+
+        test_route = test_route.layer(tower::ServiceBuilder::new()
+            .layer(axum::error_handling::HandleErrorLayer::new(middleware::overloaded))
+            .load_shed()
+            .layer(tower::limit::GlobalConcurrencyLimitLayer::with_semaphore(permits)));
+    }
 
+    let test_route = test_route
+        .layer(axum::middleware::from_fn_with_state(state.clone(), middleware::instance_header))
+        .layer(axum::middleware::from_fn(middleware::echo_headers))
+        .with_state(state);
 
+    
+    // Bind the Unix socket first, so that it's ready by the time we
+    // report that we're listening.
+    #[cfg(unix)]
+    if let Some(path) = &args.uds_path {
+        let listener = match bind_unix_socket(path) {
+            Ok(listener) => listener,
+            Err(e) => {
+                event!(Level::ERROR, "Unable to serve on the Unix socket {}: {:#}", path.display(), e);
+                std::process::exit(1);
+            }
+        };
+        event!(Level::INFO, "Serving on the Unix socket {}", path.display());
 
+        tokio::spawn(server::serve_unix(listener, test_route.clone(), args.http2));
+    }
 
-    // // Construct the address to host HTTP requests from.
-    // let serve_socket = SocketAddr::from_str(serve_address.as_str()).unwrap();
+    let axum_listener = tokio::net::TcpListener::bind(serve_address).await.unwrap();
+
+    // Report the address we actually bound, which differs from the
+    // configured one when port 0 asks for any free port.
+    let local_address = axum_listener.local_addr().unwrap();
+    event!(Level::INFO, "Listening on {}", local_address);
 
-    // // Set up the HTTP listener.
-    // let listener = TcpListener::bind(serve_socket).await?;
+    let route_paths: Vec<&str> = ROUTES.iter().map(|route| route.path).collect();
+    print_startup_banner(&args, &route_paths, local_address);
 
-    // // We start a loop to continuously accept incoming connections
-    // event!(Level::DEBUG, "Serving requests at {}", serve_address);
-    // loop {
-    //     let (stream, _) = listener.accept().await?;
+    tokio::spawn(log_liveness());
 
-    //     // Use an adapter to access something implementing `tokio::io` traits as if they implement
-    //     // `hyper::rt` IO traits.
-    //     let io = TokioIo::new(stream);
+    event!(Level::DEBUG, "Serving requests...");
+    tokio::select! {
+        _ = server::serve(axum_listener, test_route, &args) => {}
+        _ = shutdown_signal() => {
+            event!(Level::INFO, "Shutting down.");
+        }
+    }
 
-    //     // Spawn a tokio task to serve multiple connections concurrently
-    //     tokio::task::spawn(async move {
-    //         // Finally, we bind the incoming connection to our `hello` service
-    //         if let Err(err) = http1::Builder::new()
-    //             // `service_fn` converts our function in a `Service`
-    //             .serve_connection(io, service_fn(request_handler))
-    //             .await
-    //         {
-    //             eprintln!("Error serving connection: {:?}", err);
-    //         }
-    //     });
-    // }
-}
\ No newline at end of file
+    if let Some(path) = &args.uds_path {
+        if let Err(e) = std::fs::remove_file(path) {
+            event!(Level::ERROR, "Error - could not remove the Unix socket {}: {}", path.display(), e);
+        }
+    }
+}
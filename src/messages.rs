@@ -1,17 +1,21 @@
+// This module mirrors the ChatSurfer API schema, so not every structure is
+// used by the mock server, and the enum variants keep the API's spelling.
+#![allow(dead_code, clippy::upper_case_acronyms)]
+
 use anyhow::{
     Context,
     Result,
 };
 
-use http::StatusCode;
 use serde::{ Deserialize, Serialize };
+use sha2::{ Digest, Sha256 };
 use std::{
     collections::HashMap,
     fmt
 };
-use strum_macros::{ EnumString, Display };
-use tracing::{ event, Level };
-use uuid::Uuid;
+use strum_macros::{ EnumIter, EnumString, Display };
+
+use crate::timestamp::{ self, Timestamp };
 
 /// The ChatSurfer API limits client requests to a certain number
 /// every minute.
@@ -69,6 +73,15 @@ impl fmt::Display for ErrorCode400 {
 }
 
 impl ErrorCode400 {
+    /// This function returns the error ChatSurfer sends for a request
+    /// with an empty body.
+    pub fn body_missing() -> ErrorCode400 {
+        ErrorCode400 {
+            message:    String::from("Request body is missing or not readable."),
+            ..Default::default()
+        }
+    }
+
     pub fn test(source: String) -> ErrorCode400 {
         ErrorCode400 {
             classification: String::from(UNCLASSIFIED_STRING),
@@ -83,8 +96,8 @@ impl ErrorCode400 {
     pub fn try_to_json(&self) -> Result<String, anyhow::Error> {
         // Attempt to serialize the ErrorCode400 struct, and return
         // any error with a context message.
-        Ok(serde_json::to_string(self)
-            .context("Unable to convert the ErrorCode400 struct to a string.")?)
+        serde_json::to_string(self)
+            .context("Unable to convert the ErrorCode400 struct to a string.")
     }
     
     /// This method attempts to construct a ErrorCode400
@@ -92,11 +105,54 @@ impl ErrorCode400 {
     /// 
     /// If a failure occurs, the None variant will be returned.
     pub fn try_from_string(source: String) -> Result<ErrorCode400, anyhow::Error> {
-        Ok(serde_json::from_str::<ErrorCode400>(&source)
-            .with_context(|| format!("Unable to create ErrorCode400 struct from String {}", source))?)
+        serde_json::from_str::<ErrorCode400>(&source)
+            .with_context(|| format!("Unable to create ErrorCode400 struct from String {}", source))
     } // end try_from_string
+
+    /// This function searches the error chain for a JSON error which
+    /// names the field that could not be deserialized.
+    fn offending_field(error: &anyhow::Error) -> Option<String> {
+        let json_error = error.chain()
+            .find_map(|cause| cause.downcast_ref::<serde_json::Error>())?;
+        let description = json_error.to_string();
+
+        // serde reports field problems as, for example,
+        // "missing field `roomName` at line 1 column 2".
+        ["missing field `", "unknown field `", "duplicate field `"]
+            .iter()
+            .find_map(|prefix| description.strip_prefix(prefix))
+            .and_then(|rest| rest.split('`').next())
+            .map(String::from)
+    }
 } // end ErrorCode400
 
+/// Implement the trait From<anyhow::Error> for the struct ErrorCode400
+/// so that a failure to parse a request can be returned to the client
+/// as a populated 400 Bad Request body.  Only the root cause is reported,
+/// which names the offending field or value, since the context around it
+/// can quote the whole request.
+impl From<anyhow::Error> for ErrorCode400 {
+    fn from(error: anyhow::Error) -> Self {
+        let mut field_errors = Vec::new();
+        let cause = error.root_cause().to_string();
+
+        if let Some(field_name) = ErrorCode400::offending_field(&error) {
+            field_errors.push(FieldErrorSchema {
+                field_name,
+                message:            cause.clone(),
+                message_code:       String::from("RequestBodyIsInvalid"),
+                ..Default::default()
+            });
+        }
+
+        ErrorCode400 {
+            field_errors,
+            message:    format!("The request body is invalid: {}", cause),
+            ..Default::default()
+        }
+    }
+}
+
 //==============================================================================
 // ErrorCode404
 //==============================================================================
@@ -129,8 +185,8 @@ impl ErrorCode404 {
     /// 
     /// If a failure occurs, the None variant will be returned.
     pub fn try_from_string(source: String) -> Result<ErrorCode404, anyhow::Error> {
-        Ok(serde_json::from_str::<ErrorCode404>(&source)
-            .with_context(|| format!("Unable to create ErrorCode404 struct from String {}", source))?)
+        serde_json::from_str::<ErrorCode404>(&source)
+            .with_context(|| format!("Unable to create ErrorCode404 struct from String {}", source))
     }
     
     /// This method constructs a JSON string from the
@@ -138,8 +194,113 @@ impl ErrorCode404 {
     pub fn try_to_json(&self) -> Result<String, anyhow::Error> {
         // Attempt to serialize the ErrorCode404 struct, and return
         // any error with a context message.
-        Ok(serde_json::to_string(self)
-            .context("Unable to convert the ErrorCode404 struct to a string.")?)
+        serde_json::to_string(self)
+            .context("Unable to convert the ErrorCode404 struct to a string.")
+    }
+}
+
+//==============================================================================
+// ErrorCode403
+//==============================================================================
+
+/// This structure represents an HTTP 403 Forbidden message received
+/// from ChatSurfer, such as when a user requests data above their
+/// clearance.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ErrorCode403 {
+    pub classification: String,
+    pub code:           u16,
+    pub message:        String
+}
+
+impl Default for ErrorCode403 {
+    fn default() -> Self {
+        ErrorCode403 {
+            classification: String::from(UNCLASSIFIED_STRING),
+            code:           403,
+            message:        String::from("Forbidden"),
+        }
+    }
+}
+
+impl std::fmt::Display for ErrorCode403 {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let display_string = match self.try_to_json() {
+            Ok(string) => string,
+            Err(e) => e.to_string()
+        };
+
+        write!(f, "{}", display_string)
+    }
+}
+
+impl std::error::Error for ErrorCode403 {}
+
+impl ErrorCode403 {
+    /// This method attempts to construct a ErrorCode403
+    /// structure from the given JSON String parameter.
+    pub fn try_from_string(source: String) -> Result<ErrorCode403, anyhow::Error> {
+        serde_json::from_str::<ErrorCode403>(&source)
+            .with_context(|| format!("Unable to create ErrorCode403 struct from String {}", source))
+    }
+    
+    /// This method constructs a JSON string from the
+    /// ErrorCode403's fields.
+    pub fn try_to_json(&self) -> Result<String, anyhow::Error> {
+        serde_json::to_string(self)
+            .context("Unable to convert the ErrorCode403 struct to a string.")
+    }
+}
+
+//==============================================================================
+// ErrorCode401
+//==============================================================================
+
+/// This structure represents an HTTP 401 Unauthorized message received
+/// from ChatSurfer, such as when a request is missing its API key.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ErrorCode401 {
+    pub classification: String,
+    pub code:           u16,
+    pub message:        String
+}
+
+impl Default for ErrorCode401 {
+    fn default() -> Self {
+        ErrorCode401 {
+            classification: String::from(UNCLASSIFIED_STRING),
+            code:           401,
+            message:        String::from("Unauthorized"),
+        }
+    }
+}
+
+impl std::fmt::Display for ErrorCode401 {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let display_string = match self.try_to_json() {
+            Ok(string) => string,
+            Err(e) => e.to_string()
+        };
+
+        write!(f, "{}", display_string)
+    }
+}
+
+impl std::error::Error for ErrorCode401 {}
+
+impl ErrorCode401 {
+    /// This method attempts to construct a ErrorCode401
+    /// structure from the given JSON String parameter.
+    pub fn try_from_string(source: String) -> Result<ErrorCode401, anyhow::Error> {
+        serde_json::from_str::<ErrorCode401>(&source)
+            .with_context(|| format!("Unable to create ErrorCode401 struct from String {}", source))
+    }
+    
+    /// This method constructs a JSON string from the
+    /// ErrorCode401's fields.
+    pub fn try_to_json(&self) -> Result<String, anyhow::Error> {
+        serde_json::to_string(self)
+            .context("Unable to convert the ErrorCode401 struct to a string.")
     }
 }
 
@@ -193,15 +354,15 @@ impl GetApiResponse {
     /// 
     /// If a failure occurs, the None variant will be returned.
     pub fn try_from_json(json: String) -> Result<GetApiResponse, anyhow::Error> {
-        Ok(serde_json::from_str::<GetApiResponse>(&json)
-            .with_context(|| format!("Unable to create GetApiResponse struct from String {}", json))?)
+        serde_json::from_str::<GetApiResponse>(&json)
+            .with_context(|| format!("Unable to create GetApiResponse struct from String {}", json))
     }
     
     /// This method constructs a JSON string from the
     /// GetApiResponse's fields.
     pub fn try_to_json(&self) -> Result<String, anyhow::Error> {
-        Ok(serde_json::to_string(self)
-            .context("Unable to convert the GetApiResponse struct to a string.")?)
+        serde_json::to_string(self)
+            .context("Unable to convert the GetApiResponse struct to a string.")
     }
 } // end GetApiResponse
 
@@ -256,17 +417,73 @@ impl fmt::Display for SendChatMessageRequest {
 
 impl SendChatMessageRequest {
     pub fn from_string(json: String) -> SendChatMessageRequest {
-        serde_json::from_str(&json.as_str()).unwrap()
+        serde_json::from_str(json.as_str()).unwrap()
+    }
+
+    /// This method attempts to construct a SendChatMessageRequest
+    /// structure from the given JSON String parameter.
+    pub fn try_from_string(json: String) -> Result<SendChatMessageRequest, anyhow::Error> {
+        serde_json::from_str::<SendChatMessageRequest>(&json)
+            .with_context(|| format!("Unable to create SendChatMessageRequest struct from String {}", json))
     }
 
     /// This method constructs a JSON string from the
     /// SendChatMessageRequest's fields.
     pub fn try_to_json(&self) -> Result<String, anyhow::Error> {
-        Ok(serde_json::to_string(self)
-            .context("Unable to convert the SendChatMessageRequest struct to a string.")?)
+        serde_json::to_string(self)
+            .context("Unable to convert the SendChatMessageRequest struct to a string.")
     }
 } //end SendChatMessageRequest
 
+// =============================================================================
+// struct BulkSendChatMessagesResponse
+// =============================================================================
+
+/// The BulkSendChatMessagesResponse structure summarizes the outcome of a
+/// bulk send request.  The mock server accepts the valid entries of a
+/// batch even when others are rejected.
+#[derive(Serialize, Deserialize, Default)]
+pub struct BulkSendChatMessagesResponse {
+    pub accepted:   usize,
+    pub rejected:   usize,
+    pub errors:     Vec<BulkSendError>,
+}
+
+/// Implement the trait fmt::Display for the struct
+/// BulkSendChatMessagesResponse so that these structs can be easily
+/// printed to consoles.
+impl fmt::Display for BulkSendChatMessagesResponse {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let display_string = match self.try_to_json() {
+            Ok(string) => string,
+            Err(e) => e.to_string()
+        };
+
+        write!(f, "{}", display_string)
+    }
+}
+
+impl BulkSendChatMessagesResponse {
+    /// This method constructs a JSON string from the
+    /// BulkSendChatMessagesResponse's fields.
+    pub fn try_to_json(&self) -> Result<String, anyhow::Error> {
+        serde_json::to_string(self)
+            .context("Unable to convert the BulkSendChatMessagesResponse struct to a string.")
+    }
+} // end BulkSendChatMessagesResponse
+
+/// The BulkSendError structure describes why one entry of a bulk send
+/// request was rejected.
+#[derive(Serialize, Deserialize)]
+pub struct BulkSendError {
+    // The position of the rejected entry within the request.
+    pub index:          usize,
+
+    #[serde(rename = "fieldErrors")]
+    pub field_errors:   Vec<FieldErrorSchema>,
+    pub message:        String,
+}
+
 // =============================================================================
 // GetChatMessagesResponse
 // =============================================================================
@@ -286,6 +503,11 @@ pub struct GetChatMessagesResponse {
     
     #[serde(rename = "roomName")]
     pub room_name:      String,
+
+    // This field holds the number of messages available, and is only
+    // present when the response was truncated.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub total:          Option<usize>,
 }
 
 impl fmt::Display for GetChatMessagesResponse {
@@ -307,17 +529,18 @@ impl GetChatMessagesResponse {
             domain_id:      source.clone(),
             private:        false,
             room_name:      source,
+            total:          None,
         }
     }
 
     pub fn try_from_string(source: String) -> Result<GetChatMessagesResponse, anyhow::Error> {
-        Ok(serde_json::from_str::<GetChatMessagesResponse>(&source)
-            .with_context(|| format!("Unable to create GetChatMessagesResponse struct from String {}", source))?)
+        serde_json::from_str::<GetChatMessagesResponse>(&source)
+            .with_context(|| format!("Unable to create GetChatMessagesResponse struct from String {}", source))
     }
 
     pub fn try_to_json(&self) -> Result<String, anyhow::Error> {
-        Ok(serde_json::to_string(self)
-            .context("Unable to convert the GetChatMessagesResponse struct to a string.")?)
+        serde_json::to_string(self)
+            .context("Unable to convert the GetChatMessagesResponse struct to a string.")
     }
 } // end GetChatMessagesResponse
 
@@ -370,10 +593,19 @@ pub struct SearchChatMessagesRequest {
     #[serde(rename = "userIdFilter")]
     pub user_id_filter:     Option<UserIdFilter>,
 
-    #[serde(rename = "UserHighClassification")]
+    // Requests copied from the real API spell this field in camelCase,
+    // and some leave it out.
+    #[serde(rename = "UserHighClassification", alias = "userHighClassification",
+        default = "default_user_high_classification")]
     pub user_high_classification:   String,
 }
 
+/// A search request that doesn't give the user's classification is taken
+/// to be from an UNCLASSIFIED user.
+fn default_user_high_classification() -> String {
+    String::from(UNCLASSIFIED_STRING)
+}
+
 impl Default for SearchChatMessagesRequest {
     fn default() -> Self {
         SearchChatMessagesRequest {
@@ -412,14 +644,21 @@ impl fmt::Display for SearchChatMessagesRequest {
 
 impl SearchChatMessagesRequest {
     pub fn from_string(json: String) -> SearchChatMessagesRequest {
-        serde_json::from_str(&json.as_str()).unwrap()
+        serde_json::from_str(json.as_str()).unwrap()
+    }
+
+    /// This method attempts to construct a SearchChatMessagesRequest
+    /// structure from the given JSON String parameter.
+    pub fn try_from_string(json: String) -> Result<SearchChatMessagesRequest, anyhow::Error> {
+        serde_json::from_str::<SearchChatMessagesRequest>(&json)
+            .with_context(|| format!("Unable to create SearchChatMessagesRequest struct from String {}", json))
     }
 
     /// This method constructs a JSON string from the SearchChatMessagesRequest's
     /// fields.
     pub fn try_to_json(&self) -> Result<String, anyhow::Error> {
-        Ok(serde_json::to_string(self)
-            .context("Unable to convert the SearchChatMessagesRequest struct to a string.")?)
+        serde_json::to_string(self)
+            .context("Unable to convert the SearchChatMessagesRequest struct to a string.")
     }
 }
 
@@ -435,6 +674,11 @@ impl SearchChatMessagesRequest {
 #[derive(Serialize, Deserialize)]
 pub struct SearchChatMessagesResponse {
     pub classification:     String,
+
+    // This field maps each search keyword to the number of messages
+    // containing it, and is only present when a breakdown is requested.
+    #[serde(rename = "keywordCounts", default, skip_serializing_if = "Option::is_none")]
+    pub keyword_counts:     Option<HashMap<String, i32>>,
     pub messages:           Option<Vec<ChatMessageSchema>>,
 
     #[serde(rename = "nextCursorMark")]
@@ -462,8 +706,8 @@ impl SearchChatMessagesResponse {
     /// This method constructs a JSON string from the
     /// SearchChatMessagesResponse's fields.
     pub fn try_to_json(&self) -> Result<String, anyhow::Error> {
-        Ok(serde_json::to_string(self)
-            .context("Unable to convert the SearchChatMessagesResponse struct to a string.")?)
+        serde_json::to_string(self)
+            .context("Unable to convert the SearchChatMessagesResponse struct to a string.")
     }
 
     /// This method attempts to construct a SearchChatMessagesResponse
@@ -472,9 +716,9 @@ impl SearchChatMessagesResponse {
     /// If a failure occurs, the None variant will be returned.
     pub fn try_from_string(source: String)
         -> Result<SearchChatMessagesResponse, anyhow::Error> {
-        Ok(serde_json::from_str::<SearchChatMessagesResponse>(&source)
+        serde_json::from_str::<SearchChatMessagesResponse>(&source)
             .with_context(||
-                format!("Unable to create SearchChatMessagesRequest struct from String {}", source))?)
+                format!("Unable to create SearchChatMessagesRequest struct from String {}", source))
     } // end try_from_string
 } // end SearchChatMessagesResponse
 
@@ -521,11 +765,26 @@ pub struct ChatMessageSchema {
     
     #[serde(rename = "threadId")]
     pub thread_id:      Option<String>,
-    pub timestamp:      String,
+    pub timestamp:      Timestamp,
     
     #[serde(rename = "userId")]
     pub user_id:        String,
     pub private:        bool,
+
+    // This field numbers the messages sent on a WebSocket stream, so that
+    // clients can detect gaps.  It is absent from all other responses.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sequence:       Option<u64>,
+
+    // This field holds the message's relevance to a search, and is only
+    // included in search responses when scores are requested.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub score:          Option<u32>,
+
+    // This field holds rendering hints for the sender, and is only
+    // included when the server is asked to generate them.
+    #[serde(rename = "senderMeta", default, skip_serializing_if = "Option::is_none")]
+    pub sender_meta:    Option<SenderMetaSchema>,
 }
 
 impl fmt::Display for ChatMessageSchema {
@@ -543,38 +802,95 @@ impl ChatMessageSchema {
     pub fn test(source: String, seed: f32) -> ChatMessageSchema {
         ChatMessageSchema {
             classification: String::from("UNCLASSIFIED"),
-            domain_id:      String::from(source.clone()),
+            domain_id:      source.clone(),
             geo_tags:       Some(vec!(GeoTagSchema::test(source.clone(), seed))),
-            id:             String::from(source.clone()),
-            room_name:      String::from(source.clone()),
-            sender:         String::from(source.clone()),
-            text:           String::from(source.clone()),
-            thread_id:      Some(String::from(source.clone())),
-            timestamp:      String::from(source.clone()),
-            user_id:        String::from(source.clone()),
+            id:             source.clone(),
+            room_name:      source.clone(),
+            sender:         source.clone(),
+            text:           source.clone(),
+            thread_id:      Some(source.clone()),
+            timestamp:      Timestamp::now(),
+            user_id:        source.clone(),
             private:        false,
+            sequence:       None,
+            score:          None,
+            sender_meta:    None,
         }
     }
     
     pub fn try_to_json(&self) -> Result<String, anyhow::Error> {
-        Ok(serde_json::to_string(self)
-            .context("Unable to convert the ChatMessageSchema struct to a string.")?)
+        serde_json::to_string(self)
+            .context("Unable to convert the ChatMessageSchema struct to a string.")
+    }
+
+    /// This method returns the message as a GeoJSON Feature, whose
+    /// properties are the message's fields.  Its geometry is that of the
+    /// message's geo-tag, or a collection of them if it has several.
+    pub fn to_geojson_feature(&self) -> serde_json::Value {
+        let mut geometries: Vec<serde_json::Value> = self.geo_tags.iter()
+            .flatten()
+            .map(|geo_tag| serde_json::to_value(&geo_tag.location.aoi).unwrap())
+            .collect();
+
+        let geometry = match geometries.len() {
+            0 => serde_json::Value::Null,
+            1 => geometries.remove(0),
+            _ => serde_json::json!({
+                "type":         "GeometryCollection",
+                "geometries":   geometries,
+            }),
+        };
+
+        serde_json::json!({
+            "type":         "Feature",
+            "id":           self.id,
+            "geometry":     geometry,
+            "properties":   self,
+        })
     }
 
     pub fn try_from_json(json: String)
         -> Result<ChatMessageSchema, anyhow::Error> {
-        Ok(serde_json::from_str::<ChatMessageSchema>(&json)
+        serde_json::from_str::<ChatMessageSchema>(&json)
             .with_context(|| format!(
                 "Unable to create GetUsersRequest struct from String {}",
-                json))?
-        )
+                json))
     }
 } // end ChatMessageSchema
 
+//==============================================================================
+// SenderMetaSchema
+//==============================================================================
+/// The base of the avatar URLs given to senders.  Nothing is served there.
+pub const AVATAR_URL_BASE: &str = "https://avatars.example.com/";
+
+/// This struct holds hints for rendering a message's sender, derived from
+/// a hash of the sender's name so that each sender always gets the same.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct SenderMetaSchema {
+    #[serde(rename = "avatarUrl")]
+    pub avatar_url: String,
+
+    // The colour as #rrggbb.
+    pub color:      String,
+}
+
+impl SenderMetaSchema {
+    pub fn for_sender(sender: &str) -> SenderMetaSchema {
+        let digest = Sha256::digest(sender.as_bytes());
+
+        SenderMetaSchema {
+            avatar_url: format!("{}{}.png", AVATAR_URL_BASE, hex::encode(&digest[..8])),
+            color:      format!("#{}", hex::encode(&digest[8..11])),
+        }
+    }
+} // end SenderMetaSchema
+
 //==============================================================================
 // FieldErrorSchema
 //==============================================================================
 #[derive(Serialize, Deserialize)]
+#[derive(Default)]
 pub struct FieldErrorSchema {
     #[serde(rename = "fieldName")]
     pub field_name:         String,
@@ -590,17 +906,6 @@ pub struct FieldErrorSchema {
     pub rejected_value:     String
 }
 
-impl Default for FieldErrorSchema {
-    fn default() -> Self {
-        FieldErrorSchema {
-            field_name:         String::new(),
-            message:            String::new(),
-            message_arguments:  Vec::new(),
-            message_code:       String::new(),
-            rejected_value:     String::new(),
-        }
-    }
-}
 
 impl FieldErrorSchema {
     pub fn from_string(source: String) -> FieldErrorSchema {
@@ -614,6 +919,33 @@ impl FieldErrorSchema {
     }
 }
 
+//==============================================================================
+// Classification
+//==============================================================================
+/// This enum lists the classification levels a chat message can be marked
+/// with.  The variants are declared from lowest to highest, so comparing
+/// two levels tells us whether one dominates the other.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, EnumIter, EnumString, Display)]
+#[derive(Serialize, Deserialize)]
+pub enum Classification {
+    #[default]
+    #[strum(serialize = "UNCLASSIFIED")]
+    #[serde(rename = "UNCLASSIFIED")]
+    Unclassified,
+
+    #[strum(serialize = "CONFIDENTIAL")]
+    #[serde(rename = "CONFIDENTIAL")]
+    Confidential,
+
+    #[strum(serialize = "SECRET")]
+    #[serde(rename = "SECRET")]
+    Secret,
+
+    #[strum(serialize = "TOP SECRET")]
+    #[serde(rename = "TOP SECRET")]
+    TopSecret,
+} // end Classification
+
 //==============================================================================
 // NetworkId
 //==============================================================================
@@ -713,16 +1045,16 @@ impl LocationCoordinatesSchema {
     pub fn test(seed: f32) -> LocationCoordinatesSchema {
         LocationCoordinatesSchema {
             r#type:                 LocationType::Point,
-            point_coordinates:      vec!(seed.clone()),
-            polygon_coordinates:    vec!(vec!(seed.clone())),
+            point_coordinates:      vec!(seed),
+            polygon_coordinates:    vec!(vec!(seed)),
         }
     }
     
     /// This method constructs a JSON string from the LocationCoordinateSchema's
     /// fields.
     pub fn try_to_json(&self) -> Result<String, anyhow::Error> {
-        Ok(serde_json::to_string(self)
-            .context("Unable to convert the LocationCoordinatesSchema struct to a string.")?)
+        serde_json::to_string(self)
+            .context("Unable to convert the LocationCoordinatesSchema struct to a string.")
     } //end try_to_json
 } // end LocationCoordinatesSchema
 
@@ -743,33 +1075,107 @@ impl Default for LocationType {
     }
 } // end LocationType
 
+//==============================================================================
+// GeoLocationType
+//==============================================================================
+/// This enum selects the location type of the geo-tags on generated
+/// messages.  Mixed alternates between points and polygons.
+#[derive(Clone, Copy, Debug, Default, PartialEq, EnumString, Display)]
+#[derive(Serialize, Deserialize)]
+pub enum GeoLocationType {
+    #[strum(serialize = "point")]
+    #[serde(rename = "point")]
+    Point,
+
+    #[default]
+    #[strum(serialize = "polygon")]
+    #[serde(rename = "polygon")]
+    Polygon,
+
+    #[strum(serialize = "mixed")]
+    #[serde(rename = "mixed")]
+    Mixed,
+}
+
+impl GeoLocationType {
+    /// This method returns the location type of the geo-tag generated
+    /// from the given seed.
+    pub fn for_seed(&self, seed: i32) -> LocationType {
+        match self {
+            GeoLocationType::Point => LocationType::Point,
+            GeoLocationType::Polygon => LocationType::Polygon,
+            GeoLocationType::Mixed if seed.rem_euclid(2) == 0 => LocationType::Point,
+            GeoLocationType::Mixed => LocationType::Polygon,
+        }
+    }
+} // end GeoLocationType
+
+/// This function converts a [latitude, longitude] pair to a GeoJSON
+/// position, which puts the longitude first.
+fn geojson_position(latitude_longitude: &[f32]) -> Vec<f32> {
+    latitude_longitude.iter().rev().copied().collect()
+}
+
+/// The PointLocation structure is a GeoJSON Point geometry.
 #[derive(Clone, Serialize, Deserialize)]
 pub struct PointLocation {
+    #[serde(rename = "type")]
+    r#type: String,
 
+    // A [longitude, latitude] position.
+    coordinates: Vec<f32>,
 }
 
+impl PointLocation {
+    pub fn new(new_coordinates: Vec<f32>) -> PointLocation {
+        PointLocation {
+            r#type:         String::from("Point"),
+            coordinates:    new_coordinates
+        }
+    }
+
+    pub fn test(seed: f32) -> PointLocation {
+        PointLocation::new(vec!(seed, seed))
+    }
+}
+
+/// The PolygonLocation structure is a GeoJSON Polygon geometry with a
+/// single ring.
 #[derive(Clone, Serialize, Deserialize)]
 pub struct PolygonLocation {
     #[serde(rename = "type")]
     r#type: String,
-    coordinates: Vec<Vec<f32>>,
+
+    // A list of rings, each a list of [longitude, latitude] positions
+    // ending where it started.
+    coordinates: Vec<Vec<Vec<f32>>>,
 }
 
 impl PolygonLocation {
-    pub fn new(new_coordinates: Vec<Vec<f32>>) -> PolygonLocation {
+    /// This function constructs a polygon from the positions of its
+    /// corners, closing the ring if they don't already.
+    pub fn new(mut corners: Vec<Vec<f32>>) -> PolygonLocation {
+        if corners.first() != corners.last() {
+            corners.push(corners[0].clone());
+        }
+
         PolygonLocation {
             r#type:         String::from("Polygon"),
-            coordinates:    new_coordinates
+            coordinates:    vec!(corners),
         }
     }
 
     pub fn test(seed: f32) -> PolygonLocation {
-        PolygonLocation {
-            r#type:         String::from("Polygon"),
-            coordinates:    vec!(vec!(seed.clone())),
-        }
+        PolygonLocation::new(vec!(
+            vec!(seed + 1.0, seed + 1.0),
+            vec!(seed, seed + 1.0),
+            vec!(seed, seed),
+            vec!(seed + 1.0, seed),
+        ))
     }
 
+    /// This function returns the corners of the whole world as
+    /// [latitude, longitude] pairs.
     pub fn world_coordinates() -> Vec<Vec<f32>> {
         vec!(
             vec!(90.0, 180.0),
@@ -780,10 +1186,13 @@ impl PolygonLocation {
     }
 }
 
+/// This enum holds a location's geometry, which is serialized as plain
+/// GeoJSON.
 #[derive(Clone, Serialize, Deserialize)]
+#[serde(untagged)]
 pub enum LocationTypes {
-    Point { location: PointLocation },
-    Polygon { location: PolygonLocation },
+    Point(PointLocation),
+    Polygon(PolygonLocation),
 }
 
 //==============================================================================
@@ -815,36 +1224,53 @@ impl LocationSchema {
         coord_value:    f32,
         new_type:       LocationType
     ) -> LocationSchema {
+        let aoi = match new_type {
+            LocationType::Point => LocationTypes::Point(PointLocation::test(coord_value)),
+            LocationType::Polygon => LocationTypes::Polygon(PolygonLocation::test(coord_value)),
+        };
+
         LocationSchema {
-            aoi:    LocationTypes::Polygon { location: PolygonLocation::test(coord_value) },
+            aoi,
             r#type: new_type
         }
     }
 
 
     pub fn new_polygon() -> LocationSchema {
+        LocationSchema::from_polygon(PolygonLocation::world_coordinates())
+    }
+
+    /// This method constructs a polygon LocationSchema from the given
+    /// set of [latitude, longitude] pairs.
+    pub fn from_polygon(coordinates: Vec<Vec<f32>>) -> LocationSchema {
+        let corners = coordinates.iter()
+            .map(|corner| geojson_position(corner))
+            .collect();
+
         LocationSchema {
             r#type: LocationType::Polygon,
-            aoi:    LocationTypes::Polygon {
-                location: PolygonLocation::new(
-                    PolygonLocation::world_coordinates()
-                )
-            }
+            aoi:    LocationTypes::Polygon(PolygonLocation::new(corners)),
         }
     }
 
-    pub fn test(seed: f32) -> LocationSchema {
+    /// This method constructs a point LocationSchema from the given
+    /// [latitude, longitude] pair.
+    pub fn from_point(coordinates: Vec<f32>) -> LocationSchema {
         LocationSchema {
-            aoi:    LocationTypes::Polygon { location: PolygonLocation::test(seed) },
             r#type: LocationType::Point,
+            aoi:    LocationTypes::Point(PointLocation::new(geojson_position(&coordinates))),
         }
     }
+
+    pub fn test(seed: f32) -> LocationSchema {
+        LocationSchema::init(seed, LocationType::Point)
+    }
     
     /// This method constructs a JSON string from the LocationSchema's
     /// fields.
     pub fn try_to_json(&self) -> Result<String, anyhow::Error> {
-        Ok(serde_json::to_string(self)
-            .context("Unable to convert the LocationSchema struct to a string.")?)
+        serde_json::to_string(self)
+            .context("Unable to convert the LocationSchema struct to a string.")
     }
 } // end LocationSchema
 
@@ -880,10 +1306,10 @@ impl RegionSchema {
             RegionSchema {
                 abbreviation:   String::from("us"),
                 bounds:         vec!(seed),
-                description:    String::from(format!(
+                description:    format!(
                                     "This region {} is for testing.",
-                                    seed)),
-                name:           String::from(format!("Test region {}", seed)),
+                                    seed),
+                name:           format!("Test region {}", seed),
                 region_type:    String::from("Country")
             }
         }
@@ -901,8 +1327,8 @@ impl RegionSchema {
     /// This method constructs a JSON string from the RegionSchema's
     /// fields.
     pub fn try_to_json(&self) -> Result<String, anyhow::Error> {
-        Ok(serde_json::to_string(self)
-            .context("Unable to convert the RegionSchema struct to a string.")?)
+        serde_json::to_string(self)
+            .context("Unable to convert the RegionSchema struct to a string.")
 
     }
 } // end RegionSchema
@@ -943,17 +1369,17 @@ impl GeoTagSchema {
         GeoTagSchema {
             anchor_end:     0,
             anchor_start:   0,
-            anchor_text:    String::from(source.clone()),
+            anchor_text:    source.clone(),
             confidence:     0.0,
             location:       LocationSchema::test(seed),
             regions:        vec!(RegionSchema::test(source.clone(), seed)),
-            r#type:         String::from(source),
+            r#type:         source,
         }
     }
 
     pub fn try_to_json(&self) -> Result<String, anyhow::Error> {
-        Ok(serde_json::to_string(self)
-            .context("Unable to convert the GeoTagSchema struct to a string.")?)
+        serde_json::to_string(self)
+            .context("Unable to convert the GeoTagSchema struct to a string.")
     }
 } // end GeoTagSchema
 
@@ -983,8 +1409,8 @@ impl KeywordFilter {
     /// This method constructs a JSON string from the KeywordFilter's
     /// fields.
     pub fn try_to_json(&self) -> Result<String, anyhow::Error> {
-        Ok(serde_json::to_string(self)
-            .context("Unable to convert the KeywordFilter struct to a string.")?)
+        serde_json::to_string(self)
+            .context("Unable to convert the KeywordFilter struct to a string.")
     }
 } // end KeywordFilter
 
@@ -1034,6 +1460,17 @@ pub struct DomainFilterDetail  {
     pub domains: HashMap<String, DomainFilterProperties>,
 }
 
+impl DomainFilterDetail {
+    /// This method returns true if the given name is listed under the
+    /// given domain.
+    pub fn contains(&self, domain_id: &str, name: &str) -> bool {
+        match self.domains.get(domain_id) {
+            Some(filter) => filter.properties.iter().any(|property| property == name),
+            None => false,
+        }
+    }
+} // end DomainFilterDetail
+
 // =============================================================================
 // SortDirection
 // =============================================================================
@@ -1094,22 +1531,22 @@ pub struct ThreadIdFilter {
 /// request, these fields should be allowed to be ignored.
 #[derive(Serialize, Deserialize)]
 pub struct TimeFilterRequest {
-    #[serde(rename = "endDateTime")]
-    end_date_time:      Option<String>, //This string needs to be in DateTime format.
+    #[serde(rename = "endDateTime", default, deserialize_with = "timestamp::deserialize_optional")]
+    end_date_time:      Option<Timestamp>,
 
     #[serde(rename = "lookBackDuration")]
     look_back_duration: Option<String>,
     
-    #[serde(rename = "startDateTime")]
-    start_date_time:    Option<String>, //This string needs to be in DateTime format.
+    #[serde(rename = "startDateTime", default, deserialize_with = "timestamp::deserialize_optional")]
+    start_date_time:    Option<Timestamp>,
 }
 
 impl Default for TimeFilterRequest {
     fn default() -> Self {
         TimeFilterRequest {
-            end_date_time:      Some(String::new()),
+            end_date_time:      None,
             look_back_duration: Some(String::new()),
-            start_date_time:    Some(String::new()),
+            start_date_time:    None,
         }
     }
 }
@@ -1128,12 +1565,19 @@ impl fmt::Display for TimeFilterRequest {
 }
 
 impl TimeFilterRequest {
+
+    /// This method returns true if the timestamp falls within the filter's
+    /// start and end date times, either of which may be left open.
+    pub fn contains(&self, timestamp: &Timestamp) -> bool {
+        self.start_date_time.is_none_or(|start| start <= *timestamp)
+            && self.end_date_time.is_none_or(|end| *timestamp <= end)
+    }
     
     /// This method constructs a JSON string from the TimeFilterRequest's
     /// fields.
     pub fn try_to_json(&self) -> Result<String, anyhow::Error> {
-        Ok(serde_json::to_string(self)
-            .context("Unable to convert the TimeFilterRequest struct to a string.")?)
+        serde_json::to_string(self)
+            .context("Unable to convert the TimeFilterRequest struct to a string.")
     }
 }
 
@@ -1152,7 +1596,7 @@ pub struct UserIdFilter {
 #[derive(Serialize, Deserialize)]
 pub struct TimeFilterResponse {
     #[serde(rename = "endDateTime")]
-    pub end_date_time:  String,
+    pub end_date_time:  Timestamp,
 }
 
 
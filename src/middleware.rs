@@ -0,0 +1,322 @@
+use axum::{
+    body::{
+        Body,
+        HttpBody,
+    },
+    extract::{
+        MatchedPath,
+        Request,
+        State,
+    },
+    http::{
+        header::{
+            CONTENT_LENGTH,
+            RETRY_AFTER,
+        },
+        HeaderName,
+        HeaderValue,
+        StatusCode,
+    },
+    middleware::Next,
+    response::{
+        IntoResponse,
+        Response,
+    },
+};
+use serde::Serialize;
+use std::time::{
+    Duration,
+    Instant,
+};
+use tracing::{ event, Level };
+
+use crate::{
+    ServerState,
+    WARMUP_RETRY_AFTER_JITTER_SECS,
+    WARMUP_RETRY_AFTER_SECS,
+};
+
+/// The header identifying which server instance produced a response.
+pub const INSTANCE_HEADER: &str = "x-mock-instance";
+
+/// Request headers starting with this prefix are echoed on the response.
+pub const ECHO_HEADER_PREFIX: &str = "x-echo-";
+
+/// These headers are never echoed: they frame the response, describe its
+/// body, or describe the connection.  A second copy would break HTTP/1
+/// framing or leave clients to guess how to decode the body, and HTTP/2
+/// forbids the connection-specific ones outright.
+const UNECHOED_HEADERS: [&str; 9] = [
+    "connection",
+    "content-encoding",
+    "content-length",
+    "content-type",
+    "keep-alive",
+    "te",
+    "trailer",
+    "transfer-encoding",
+    "upgrade",
+];
+
+/// This middleware emits a single structured event for every completed
+/// request, so that latency and response sizes can be collected from the
+/// logs without each handler having to report them.
+pub async fn request_metrics(
+    request:    Request,
+    next:       Next,
+) -> Response {
+    // Prefer the route template over the raw URI so that events for the
+    // same route can be grouped together.
+    let route = match request.extensions().get::<MatchedPath>() {
+        Some(path) => path.as_str().to_string(),
+        None => request.uri().path().to_string(),
+    };
+
+    let start = Instant::now();
+    let response = next.run(request).await;
+    let duration_ms = start.elapsed().as_secs_f64() * 1000.0;
+
+    // Streaming bodies have no exact size, so report what we know.
+    let response_bytes = response.body().size_hint().exact()
+        .unwrap_or(response.body().size_hint().lower());
+
+    event!(
+        Level::INFO,
+        route,
+        status = response.status().as_u16(),
+        duration_ms,
+        response_bytes,
+        "Request complete"
+    );
+
+    response
+} // end request_metrics
+
+/// This middleware stamps every response, including WebSocket upgrades,
+/// with the configured instance name so clients behind a load balancer
+/// can tell which server answered them.
+pub async fn instance_header(
+    State(state):   State<ServerState>,
+    request:        Request,
+    next:           Next,
+) -> Response {
+    let mut response = next.run(request).await;
+
+    // The instance name was validated as a header value when the
+    // arguments were parsed.
+    if let Ok(value) = HeaderValue::from_str(&state.args.instance_name) {
+        response.headers_mut().insert(INSTANCE_HEADER, value);
+    }
+
+    response
+} // end instance_header
+
+/// This middleware copies every request header named with the echo prefix
+/// onto the response, minus the prefix, so that clients can have the mock
+/// send any header they like, short of the framing and hop-by-hop ones.
+pub async fn echo_headers(
+    request:    Request,
+    next:       Next,
+) -> Response {
+    let echoed: Vec<(HeaderName, HeaderValue)> = request.headers().iter()
+        .filter_map(|(name, value)| {
+            let stripped = name.as_str().strip_prefix(ECHO_HEADER_PREFIX)
+                .filter(|stripped| !UNECHOED_HEADERS.contains(stripped))?;
+            Some((HeaderName::from_bytes(stripped.as_bytes()).ok()?, value.clone()))
+        })
+        .collect();
+
+    let mut response = next.run(request).await;
+
+    for (name, value) in echoed {
+        response.headers_mut().append(name, value);
+    }
+
+    response
+} // end echo_headers
+
+/// This middleware answers the first requests to each data route with a
+/// 503, as a backend that is slow to warm up would, so that clients can
+/// exercise their retry logic.
+pub async fn warmup_failures(
+    State(state):   State<ServerState>,
+    request:        Request,
+    next:           Next,
+) -> Response {
+    let warming_up = request.extensions().get::<MatchedPath>()
+        .is_some_and(|path| state.is_warming_up(state.args.route_template(path.as_str())));
+
+    if !warming_up {
+        return next.run(request).await;
+    }
+
+    let retry_after = WARMUP_RETRY_AFTER_SECS
+        + state.random::<u64>() % (WARMUP_RETRY_AFTER_JITTER_SECS + 1);
+    event!(Level::DEBUG, "Warming up, asking the client to retry after {} seconds", retry_after);
+
+    (
+        StatusCode::SERVICE_UNAVAILABLE,
+        [(RETRY_AFTER, retry_after.to_string())],
+        serde_json::to_string("Service Unavailable").unwrap(),
+    ).into_response()
+} // end warmup_failures
+
+/// The LatencySpike structure describes a window that recurs during which
+/// every response is delayed, as it would be by a server pausing for
+/// garbage collection.  The window falls at the end of each period, so
+/// the server starts out fast.
+#[derive(Clone, Debug, Serialize)]
+pub struct LatencySpike {
+    // How often the spike recurs.
+    pub every:      Duration,
+
+    // The delay added to each response during the spike.
+    pub add:        Duration,
+
+    // How long the spike lasts.
+    pub duration:   Duration,
+}
+
+impl LatencySpike {
+    /// This function parses a spike given as <every>/<add>/<for>, such as
+    /// 30s/500ms/2s, where each duration is in s or ms.
+    pub fn parse(value: &str) -> Result<LatencySpike, String> {
+        let parts: Vec<&str> = value.split('/').collect();
+
+        let [every, add, duration] = parts[..] else {
+            return Err(format!("{} is not of the form <every>/<add>/<for>, such as 30s/500ms/2s", value));
+        };
+
+        let spike = LatencySpike {
+            every:      parse_duration(every)?,
+            add:        parse_duration(add)?,
+            duration:   parse_duration(duration)?,
+        };
+
+        if spike.every.is_zero() || spike.duration > spike.every {
+            return Err(format!("The spike in {} has to recur, and last no longer than its period", value));
+        }
+
+        Ok(spike)
+    }
+
+    /// This method returns true if the given time since the server started
+    /// falls within a spike.
+    pub fn is_active(&self, elapsed: Duration) -> bool {
+        let into_period = elapsed.as_millis() % self.every.as_millis();
+
+        into_period >= (self.every - self.duration).as_millis()
+    }
+} // end LatencySpike
+
+/// This function parses a duration given as a whole number of seconds or
+/// milliseconds, such as 2s or 500ms.
+fn parse_duration(value: &str) -> Result<Duration, String> {
+    let parse = |number: &str| number.parse::<u64>()
+        .map_err(|e| format!("{} is not a duration: {}", value, e));
+
+    if let Some(millis) = value.strip_suffix("ms") {
+        Ok(Duration::from_millis(parse(millis)?))
+    } else if let Some(secs) = value.strip_suffix('s') {
+        Ok(Duration::from_secs(parse(secs)?))
+    } else {
+        Err(format!("{} is not a duration in s or ms", value))
+    }
+}
+
+/// This middleware delays the responses to requests arriving during a
+/// latency spike, if one is configured.
+pub async fn latency_spike(
+    State(state):   State<ServerState>,
+    request:        Request,
+    next:           Next,
+) -> Response {
+    let spike = state.args.latency_spike.as_ref()
+        .filter(|spike| spike.is_active(state.started.elapsed()));
+
+    let response = next.run(request).await;
+
+    if let Some(spike) = spike {
+        event!(Level::DEBUG, "Delaying the response by {:?} for a latency spike", spike.add);
+        tokio::time::sleep(spike.add).await;
+    }
+
+    response
+} // end latency_spike
+
+/// This middleware delays each response by a random amount up to the
+/// configured bound, so that responses to concurrent requests can
+/// arrive in a different order than the requests were sent.
+pub async fn shuffle_latency(
+    State(state):   State<ServerState>,
+    request:        Request,
+    next:           Next,
+) -> Response {
+    let response = next.run(request).await;
+
+    if state.args.shuffle_latency_ms > 0 {
+        let delay_ms = state.random::<u64>() % state.args.shuffle_latency_ms;
+        tokio::time::sleep(Duration::from_millis(delay_ms)).await;
+    }
+
+    response
+} // end shuffle_latency
+
+
+/// This middleware rewrites JSON response bodies with their object keys
+/// sorted, when asked to, so that responses can be compared byte for byte.
+/// Streaming bodies, and bodies that aren't JSON, are left alone.
+pub async fn canonical_json(
+    State(state):   State<ServerState>,
+    request:        Request,
+    next:           Next,
+) -> Response {
+    let response = next.run(request).await;
+
+    if !state.args.canonical_json || response.body().size_hint().exact().is_none() {
+        return response;
+    }
+
+    let (mut parts, body) = response.into_parts();
+
+    let bytes = match axum::body::to_bytes(body, usize::MAX).await {
+        Ok(bytes) => bytes,
+        Err(e) => {
+            event!(Level::ERROR, "Error - could not read the response body: {}", e);
+            return (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response();
+        }
+    };
+
+    let body = match canonicalize_json(&bytes) {
+        Some(canonical) => {
+            parts.headers.insert(CONTENT_LENGTH, HeaderValue::from(canonical.len()));
+            Body::from(canonical)
+        }
+        None => Body::from(bytes),
+    };
+
+    Response::from_parts(parts, body)
+} // end canonical_json
+
+/// This function returns the canonical form of a JSON body, with its
+/// objects' keys sorted and no whitespace, or None if the body isn't JSON.
+/// Handlers that hash their body use it to hash what is actually sent.
+pub fn canonicalize_json(body: &[u8]) -> Option<String> {
+    // Without serde_json's preserve_order feature, a Value keeps its
+    // objects' keys sorted.
+    serde_json::from_slice::<serde_json::Value>(body)
+        .ok()
+        .map(|value| value.to_string())
+}
+
+/// This function answers a request that was refused because too many were
+/// already in flight, or that otherwise failed in the load shedding layers.
+pub async fn overloaded(error: tower::BoxError) -> (StatusCode, String) {
+    if error.is::<tower::load_shed::error::Overloaded>() {
+        event!(Level::DEBUG, "Shedding a request over the in-flight limit");
+        return (StatusCode::SERVICE_UNAVAILABLE, String::from("Too many requests in flight"));
+    }
+
+    event!(Level::ERROR, "Error - the request failed: {}", error);
+    (StatusCode::INTERNAL_SERVER_ERROR, error.to_string())
+} // end overloaded
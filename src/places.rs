@@ -0,0 +1,69 @@
+/// The Place struct describes a real-world location that generated
+/// geo-tags can reference, so that clients rendering the messages on a
+/// map see recognizable locations.
+pub struct Place {
+    pub name:           &'static str,
+    pub country:        &'static str,
+
+    // The ISO 3166-1 alpha-2 code of the country containing the place.
+    pub abbreviation:   &'static str,
+    pub latitude:       f32,
+    pub longitude:      f32,
+}
+
+/// The number of degrees a place's bounding box extends from its center.
+pub const PLACE_HALF_WIDTH_DEGREES: f32 = 0.25;
+
+pub const PLACES: [Place; 12] = [
+    Place { name: "New York",       country: "United States",   abbreviation: "us", latitude: 40.7128,  longitude: -74.0060 },
+    Place { name: "London",         country: "United Kingdom",  abbreviation: "gb", latitude: 51.5074,  longitude: -0.1278 },
+    Place { name: "Paris",          country: "France",          abbreviation: "fr", latitude: 48.8566,  longitude: 2.3522 },
+    Place { name: "Berlin",         country: "Germany",         abbreviation: "de", latitude: 52.5200,  longitude: 13.4050 },
+    Place { name: "Tokyo",          country: "Japan",           abbreviation: "jp", latitude: 35.6762,  longitude: 139.6503 },
+    Place { name: "Sydney",         country: "Australia",       abbreviation: "au", latitude: -33.8688, longitude: 151.2093 },
+    Place { name: "Cairo",          country: "Egypt",           abbreviation: "eg", latitude: 30.0444,  longitude: 31.2357 },
+    Place { name: "Rio de Janeiro", country: "Brazil",          abbreviation: "br", latitude: -22.9068, longitude: -43.1729 },
+    Place { name: "Mumbai",         country: "India",           abbreviation: "in", latitude: 19.0760,  longitude: 72.8777 },
+    Place { name: "Nairobi",        country: "Kenya",           abbreviation: "ke", latitude: -1.2921,  longitude: 36.8219 },
+    Place { name: "Mexico City",    country: "Mexico",          abbreviation: "mx", latitude: 19.4326,  longitude: -99.1332 },
+    Place { name: "Anchorage",      country: "United States",   abbreviation: "us", latitude: 61.2181,  longitude: -149.9003 },
+];
+
+/// This function deterministically selects a place from the built-in
+/// table, so that the same seed always yields the same location.
+pub fn place_for_seed(seed: i32) -> &'static Place {
+    &PLACES[seed.rem_euclid(PLACES.len() as i32) as usize]
+}
+
+impl Place {
+    /// This method returns the place's bounding box as
+    /// [south, west, north, east] in degrees.
+    pub fn bounds(&self) -> Vec<f32> {
+        vec!(
+            self.latitude - PLACE_HALF_WIDTH_DEGREES,
+            self.longitude - PLACE_HALF_WIDTH_DEGREES,
+            self.latitude + PLACE_HALF_WIDTH_DEGREES,
+            self.longitude + PLACE_HALF_WIDTH_DEGREES,
+        )
+    }
+
+    /// This method returns the place's centre as a [latitude, longitude]
+    /// pair.
+    pub fn point(&self) -> Vec<f32> {
+        vec!(self.latitude, self.longitude)
+    }
+
+    /// This method returns the corners of the place's bounding box as
+    /// [latitude, longitude] pairs, matching
+    /// PolygonLocation::world_coordinates.
+    pub fn polygon(&self) -> Vec<Vec<f32>> {
+        let bounds = self.bounds();
+
+        vec!(
+            vec!(bounds[2], bounds[3]),
+            vec!(bounds[2], bounds[1]),
+            vec!(bounds[0], bounds[1]),
+            vec!(bounds[0], bounds[3]),
+        )
+    }
+} // end Place
@@ -0,0 +1,48 @@
+use serde::Serialize;
+use tracing::{ event, Level };
+
+/// The geo-tag fields a client can select, named as they are in JSON.
+pub const GEO_TAG_FIELDS: [&str; 7] = [
+    "anchorEnd",
+    "anchorStart",
+    "anchorText",
+    "confidence",
+    "location",
+    "regions",
+    "type",
+];
+
+/// This function converts a response body to JSON, keeping only the
+/// selected fields of each message's geo-tags.  The selection is a comma
+/// separated list such as `location,regions`; names that aren't geo-tag
+/// fields are ignored.
+pub fn project_geo_tags<T: Serialize>(body: &T, selection: &str) -> serde_json::Value {
+    let mut fields: Vec<&str> = Vec::new();
+
+    for field in selection.split(',').map(str::trim).filter(|field| !field.is_empty()) {
+        if GEO_TAG_FIELDS.contains(&field) {
+            fields.push(field);
+        } else {
+            event!(Level::DEBUG, "Ignoring the unknown geo-tag field {:?}", field);
+        }
+    }
+
+    let mut value = serde_json::to_value(body).unwrap();
+
+    // Look fields up with get_mut rather than by index, which would add
+    // the ones that are missing.
+    let geo_tags = value.get_mut("messages")
+        .and_then(serde_json::Value::as_array_mut)
+        .into_iter()
+        .flatten()
+        .filter_map(|message| message.get_mut("geoTags"))
+        .filter_map(serde_json::Value::as_array_mut)
+        .flatten()
+        .filter_map(|geo_tag| geo_tag.as_object_mut());
+
+    for geo_tag in geo_tags {
+        geo_tag.retain(|name, _| fields.contains(&name.as_str()));
+    }
+
+    value
+} // end project_geo_tags
@@ -0,0 +1,216 @@
+use serde::{ Deserialize, Serialize };
+use std::collections::{
+    HashMap,
+    VecDeque,
+};
+use uuid::Uuid;
+
+use crate::{
+    cursor::CursorSigner,
+    messages::ChatMessageSchema,
+};
+
+/// The number of sent messages kept for each stream, so that a client
+/// resuming the stream can be sent the ones it missed.
+pub const RESUME_BUFFER_CAPACITY: usize = 100;
+
+/// The number of streams that can be resumed.  Beyond this, the buffers of
+/// the oldest streams are discarded.
+pub const MAX_RESUMABLE_STREAMS: usize = 100;
+
+/// The tag mixed into the HMAC of resume tokens, so that a search cursor
+/// is never accepted as a resume token, nor a resume token as a cursor.
+const RESUME_PURPOSE: &[u8] = b"resume\0";
+
+/// The RecycleRange structure bounds the number of messages sent on a
+/// WebSocket connection before the server closes it, so that clients have
+/// to reconnect and resume.  Each connection draws its own count.
+#[derive(Clone, Debug, Serialize)]
+pub struct RecycleRange {
+    pub min:    u64,
+    pub max:    u64,
+}
+
+impl RecycleRange {
+    /// This function parses a range given as <min>-<max>, such as 50-100,
+    /// or as a single count.
+    pub fn parse(value: &str) -> Result<RecycleRange, String> {
+        let (min, max) = value.split_once('-').unwrap_or((value, value));
+
+        let parse = |count: &str| count.trim().parse::<u64>()
+            .map_err(|_| format!("{} is not of the form <min>-<max>, such as 50-100", value));
+
+        let range = RecycleRange {
+            min:    parse(min)?,
+            max:    parse(max)?,
+        };
+
+        if range.min == 0 || range.min > range.max {
+            return Err(format!("The range {} has to start above 0 and end after it starts", value));
+        }
+
+        Ok(range)
+    }
+} // end RecycleRange
+
+/// The ResumeFrame structure is sent periodically on a WebSocket stream.
+/// A client that reconnects with its token continues the same stream,
+/// starting with the buffered messages it has not yet seen.
+///
+/// `{"type":"resume","token":"..."}`
+#[derive(Serialize, Deserialize)]
+pub struct ResumeFrame {
+    // This field is always "resume", distinguishing these frames from
+    // chat messages.
+    pub r#type: String,
+
+    // Clients should treat the token as opaque.
+    pub token:  String,
+}
+
+impl ResumeFrame {
+    pub fn new(token: &ResumeToken, signer: &CursorSigner) -> ResumeFrame {
+        ResumeFrame {
+            r#type: String::from("resume"),
+            token:  token.encode(signer),
+        }
+    }
+} // end ResumeFrame
+
+/// The ResumeFailedFrame structure is sent first on a connection whose
+/// resume token couldn't be honored, so that the client knows the stream
+/// it is given is a new one, numbered from 0, and that it may have missed
+/// messages.
+///
+/// `{"type":"resume_failed","reason":"..."}`
+#[derive(Serialize, Deserialize)]
+pub struct ResumeFailedFrame {
+    // This field is always "resume_failed".
+    pub r#type: String,
+    pub reason: String,
+}
+
+impl ResumeFailedFrame {
+    pub fn new(reason: &str) -> ResumeFailedFrame {
+        ResumeFailedFrame {
+            r#type: String::from("resume_failed"),
+            reason: String::from(reason),
+        }
+    }
+} // end ResumeFailedFrame
+
+/// The ResumeToken structure identifies a stream and the last message the
+/// client was sent on it.  Tokens are signed like search cursors, but for
+/// a purpose of their own, so that a client can't resume a stream it
+/// wasn't given.
+pub struct ResumeToken {
+    pub stream_id:  String,
+    pub sequence:   u64,
+}
+
+impl ResumeToken {
+    pub fn encode(&self, signer: &CursorSigner) -> String {
+        let mut payload = self.sequence.to_be_bytes().to_vec();
+        payload.extend_from_slice(self.stream_id.as_bytes());
+
+        signer.sign(RESUME_PURPOSE, &payload)
+    }
+
+    /// This method parses a token produced by encode, returning None if
+    /// the token is malformed or has been altered.
+    pub fn decode(token: &str, signer: &CursorSigner) -> Option<ResumeToken> {
+        let payload = signer.verify(RESUME_PURPOSE, token)?;
+        let (sequence, stream_id) = payload.split_first_chunk::<8>()?;
+
+        Some(ResumeToken {
+            stream_id:  String::from_utf8(stream_id.to_vec()).ok()?,
+            sequence:   u64::from_be_bytes(*sequence),
+        })
+    }
+} // end ResumeToken
+
+/// The StreamBuffer structure numbers the messages sent on a stream, and
+/// holds on to the most recent of them in case the stream is resumed.
+#[derive(Default)]
+pub struct StreamBuffer {
+    next_sequence:  u64,
+    messages:       VecDeque<ChatMessageSchema>,
+}
+
+impl StreamBuffer {
+    /// This method assigns the message the stream's next sequence number
+    /// and buffers it, evicting the oldest message if the buffer is full.
+    pub fn push(&mut self, mut message: ChatMessageSchema) -> ChatMessageSchema {
+        message.sequence = Some(self.next_sequence);
+        self.next_sequence += 1;
+
+        if self.messages.len() == RESUME_BUFFER_CAPACITY {
+            self.messages.pop_front();
+        }
+        self.messages.push_back(message.clone());
+
+        message
+    }
+
+    /// This method returns the buffered messages sent after the given
+    /// sequence number.
+    pub fn since(&self, sequence: u64) -> Vec<ChatMessageSchema> {
+        self.messages.iter()
+            .filter(|message| message.sequence.is_some_and(|sent| sent > sequence))
+            .cloned()
+            .collect()
+    }
+} // end StreamBuffer
+
+/// The StreamBuffers structure holds the buffers of the most recently
+/// opened streams.
+#[derive(Default)]
+pub struct StreamBuffers {
+    buffers:    HashMap<String, StreamBuffer>,
+
+    // The stream ids, oldest first.
+    order:      VecDeque<String>,
+}
+
+impl StreamBuffers {
+    /// This method continues the stream named by the resume token, if it
+    /// is still buffered, or else opens a new one.  It returns the stream's
+    /// id and, if the stream was continued, the messages the client has yet
+    /// to be sent.
+    pub fn open(&mut self, resume: Option<ResumeToken>) -> (String, Option<Vec<ChatMessageSchema>>) {
+        if let Some(token) = resume {
+            if let Some(buffer) = self.buffers.get(&token.stream_id) {
+                let missed = buffer.since(token.sequence);
+                return (token.stream_id, Some(missed));
+            }
+        }
+
+        let stream_id = Uuid::new_v4().to_string();
+        self.insert(&stream_id);
+
+        (stream_id, None)
+    }
+
+    /// This method numbers and buffers a message sent on the given stream.
+    /// A stream that has since been discarded is started again.
+    pub fn push(&mut self, stream_id: &str, message: ChatMessageSchema) -> ChatMessageSchema {
+        if !self.buffers.contains_key(stream_id) {
+            self.insert(stream_id);
+        }
+
+        self.buffers.get_mut(stream_id).unwrap().push(message)
+    }
+
+    /// This method adds an empty buffer for the given stream, discarding
+    /// the oldest stream's buffer if there are too many.
+    fn insert(&mut self, stream_id: &str) {
+        if self.order.len() == MAX_RESUMABLE_STREAMS {
+            if let Some(oldest) = self.order.pop_front() {
+                self.buffers.remove(&oldest);
+            }
+        }
+
+        self.order.push_back(String::from(stream_id));
+        self.buffers.insert(String::from(stream_id), StreamBuffer::default());
+    }
+} // end StreamBuffers
@@ -0,0 +1,106 @@
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::Ordering,
+        Mutex,
+    },
+};
+use tokio::{
+    sync::broadcast,
+    time::Instant,
+};
+use tracing::{ event, Level };
+
+use crate::{
+    build_chat_message,
+    demo,
+    messages::ChatMessageSchema,
+    ServerState,
+    BROADCAST_CAPACITY,
+};
+
+/// The RoomFeeds structure holds the feed of generated messages for each
+/// room with WebSocket subscribers.  Every subscriber to a room receives
+/// the same messages, as they would from a real shared room.
+#[derive(Default)]
+pub struct RoomFeeds {
+    feeds:  Mutex<HashMap<String, broadcast::Sender<Vec<ChatMessageSchema>>>>,
+}
+
+/// This function subscribes to the feed of the given room, starting the
+/// room's generator if it has no other subscribers.  Each item on the feed
+/// is one push of generated messages.
+pub fn subscribe(state: &ServerState, room: &str) -> broadcast::Receiver<Vec<ChatMessageSchema>> {
+    let mut feeds = state.rooms.feeds.lock().unwrap();
+
+    if let Some(sender) = feeds.get(room) {
+        return sender.subscribe();
+    }
+
+    let (sender, receiver) = broadcast::channel(BROADCAST_CAPACITY);
+    feeds.insert(String::from(room), sender.clone());
+    tokio::spawn(generate(state.clone(), String::from(room), sender));
+
+    event!(Level::DEBUG, "Started the message generator for {}", room);
+    receiver
+} // end subscribe
+
+/// This function generates the room's messages at the WebSocket push
+/// interval, stopping once the room has no subscribers left.
+async fn generate(
+    state:  ServerState,
+    room:   String,
+    sender: broadcast::Sender<Vec<ChatMessageSchema>>,
+) {
+    // In demo mode, the generated messages follow a script rather than
+    // being random.  The script is the room's, like its other messages,
+    // so a subscriber joining later picks it up partway through.
+    let mut demo = state.args.demo_mode.then(demo::DemoGenerator::new);
+
+    // The push interval can be changed while the server runs, so the
+    // time of the next push is worked out afresh on every iteration.
+    let mut last_generated = Instant::now();
+
+    loop {
+        tokio::time::sleep_until(last_generated + state.ws_interval()).await;
+        last_generated = Instant::now();
+
+        // Check under the lock, so that nobody can subscribe between the
+        // check and the feed's removal.
+        {
+            let mut feeds = state.rooms.feeds.lock().unwrap();
+
+            if sender.receiver_count() == 0 {
+                feeds.remove(&room);
+                event!(Level::DEBUG, "Stopped the message generator for {}", room);
+                return;
+            }
+        }
+
+        let batch = (0..state.args.ws_batch_size)
+            .map(|_| match demo.as_mut() {
+                Some(demo) => {
+                    state.messages_generated.fetch_add(1, Ordering::Relaxed);
+
+                    demo.next_message(
+                        state.args.classification.to_string(),
+                        state.args.geo_location_type,
+                        state.args.sender_meta)
+                }
+                None => {
+                    let random_seed = state.random::<i32>();
+
+                    build_chat_message(
+                        &state,
+                        random_seed,
+                        "Austin",
+                        random_seed.to_string().as_str()
+                    )
+                }
+            })
+            .collect();
+
+        // Every subscriber may have left since the check, which is fine.
+        let _ = sender.send(batch);
+    }
+} // end generate
@@ -0,0 +1,93 @@
+use anyhow::{
+    bail,
+    Context,
+};
+use std::collections::HashMap;
+
+use crate::{
+    messages::{
+        ChatMessageSchema,
+        ErrorCode400,
+        ErrorCode404,
+        GetChatMessagesResponse,
+        SearchChatMessagesResponse,
+        SenderMetaSchema,
+        TimeFilterResponse,
+    },
+    timestamp::Timestamp,
+    UNCLASSIFIED_STRING,
+};
+
+/// The text the sample structures are filled in with.
+const SAMPLE_SOURCE: &str = "self-test";
+
+/// This function round-trips a sample of each of the main structures we
+/// send through JSON, failing if any comes back different.  A field whose
+/// name differs between serializing and deserializing, for example, would
+/// be lost on the way back.
+pub fn run() -> Result<(), anyhow::Error> {
+    let mut message = ChatMessageSchema::test(String::from(SAMPLE_SOURCE), 0.5);
+    message.sequence = Some(1);
+    message.score = Some(2);
+    message.sender_meta = Some(SenderMetaSchema::for_sender(SAMPLE_SOURCE));
+
+    let mut get_response = GetChatMessagesResponse::test(String::from(SAMPLE_SOURCE));
+    get_response.messages = vec!(message.clone());
+    get_response.total = Some(1);
+
+    let search_response = SearchChatMessagesResponse {
+        classification:     String::from(UNCLASSIFIED_STRING),
+        keyword_counts:     Some(HashMap::from([(String::from(SAMPLE_SOURCE), 1)])),
+        messages:           Some(vec!(message.clone())),
+        next_cursor_mark:   Some(String::from(SAMPLE_SOURCE)),
+        search_time_filter: TimeFilterResponse {
+            end_date_time:  Timestamp::now(),
+        },
+        total:              1,
+    };
+
+    let not_found = ErrorCode404 {
+        classification: String::from(UNCLASSIFIED_STRING),
+        code:           404,
+        message:        String::from(SAMPLE_SOURCE),
+    };
+
+    round_trip("ChatMessageSchema", &message,
+        ChatMessageSchema::try_to_json, ChatMessageSchema::try_from_json)?;
+    round_trip("GetChatMessagesResponse", &get_response,
+        GetChatMessagesResponse::try_to_json, GetChatMessagesResponse::try_from_string)?;
+    round_trip("SearchChatMessagesResponse", &search_response,
+        SearchChatMessagesResponse::try_to_json, SearchChatMessagesResponse::try_from_string)?;
+    round_trip("ErrorCode400", &ErrorCode400::test(String::from(SAMPLE_SOURCE)),
+        ErrorCode400::try_to_json, ErrorCode400::try_from_string)?;
+    round_trip("ErrorCode404", &not_found,
+        ErrorCode404::try_to_json, ErrorCode404::try_from_string)?;
+
+    Ok(())
+} // end run
+
+/// This function converts the sample to JSON, back again, and to JSON once
+/// more, checking that both JSON documents are the same.
+fn round_trip<T>(
+    name:           &str,
+    sample:         &T,
+    to_json:        fn(&T) -> Result<String, anyhow::Error>,
+    from_string:    fn(String) -> Result<T, anyhow::Error>,
+) -> Result<(), anyhow::Error> {
+    let json = to_json(sample)
+        .with_context(|| format!("The {} sample could not be serialized", name))?;
+    let parsed = from_string(json.clone())
+        .with_context(|| format!("The {} sample could not be deserialized", name))?;
+    let round_tripped = to_json(&parsed)
+        .with_context(|| format!("The deserialized {} sample could not be serialized", name))?;
+
+    // Compare the documents as values, as the order of map keys may vary.
+    let expected: serde_json::Value = serde_json::from_str(&json)?;
+    let actual: serde_json::Value = serde_json::from_str(&round_tripped)?;
+
+    if expected != actual {
+        bail!("The {} sample changed on its way through JSON, from {} to {}", name, json, round_tripped);
+    }
+
+    Ok(())
+} // end round_trip
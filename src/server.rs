@@ -0,0 +1,212 @@
+use anyhow::Context;
+use axum::Router;
+use hyper::server::conn::http1;
+use hyper_util::{
+    rt::{
+        TokioExecutor,
+        TokioIo,
+    },
+    server::conn::auto,
+    service::TowerToHyperService,
+};
+use socket2::{
+    SockRef,
+    TcpKeepalive,
+};
+use std::time::Duration;
+use tokio::{
+    io::{
+        AsyncRead,
+        AsyncWrite,
+    },
+    net::{
+        TcpListener,
+        TcpStream,
+    },
+    time::Instant,
+};
+#[cfg(unix)]
+use tokio::net::UnixListener;
+use tower::Layer;
+use tower_http::normalize_path::NormalizePathLayer;
+use tracing::{ event, Level };
+
+use crate::Args;
+
+/// The AcceptLimiter structure is a token bucket gating how quickly
+/// connections are accepted.  It holds a single token, so connections are
+/// accepted evenly spaced rather than in bursts.
+struct AcceptLimiter {
+    interval:   Duration,
+
+    // When the bucket next holds a token.
+    next_token: Instant,
+}
+
+impl AcceptLimiter {
+    fn new(rate_per_sec: u64) -> AcceptLimiter {
+        AcceptLimiter {
+            interval:   Duration::from_secs_f64(1.0 / rate_per_sec as f64),
+            next_token: Instant::now(),
+        }
+    }
+
+    /// This method waits until the bucket holds a token, and takes it.
+    async fn acquire(&mut self) {
+        tokio::time::sleep_until(self.next_token).await;
+        self.next_token = Instant::now() + self.interval;
+    }
+} // end AcceptLimiter
+
+/// This function applies the configured socket options to a newly
+/// accepted connection.
+fn configure_stream(
+    stream: &TcpStream,
+    args:   &Args,
+) -> Result<(), anyhow::Error> {
+    stream.set_nodelay(args.tcp_nodelay)
+        .context("Unable to set TCP_NODELAY")?;
+
+    if let Some(seconds) = args.tcp_keepalive_secs {
+        let keepalive = TcpKeepalive::new()
+            .with_time(Duration::from_secs(seconds))
+            .with_interval(Duration::from_secs(seconds));
+
+        SockRef::from(stream).set_tcp_keepalive(&keepalive)
+            .context("Unable to set TCP keepalive")?;
+    }
+
+    Ok(())
+} // end configure_stream
+
+/// This function accepts connections from the listener and serves the
+/// router on each of them.  We accept the connections ourselves, rather
+/// than through axum::serve, so that we can configure each socket and
+/// limit the rate they're accepted at.
+pub async fn serve(
+    listener:   TcpListener,
+    router:     Router,
+    args:       &Args,
+) {
+    let mut limiter = args.accept_rate_per_sec.map(AcceptLimiter::new);
+
+    loop {
+        if let Some(limiter) = limiter.as_mut() {
+            limiter.acquire().await;
+        }
+
+        let (stream, remote_address) = match listener.accept().await {
+            Ok(connection) => connection,
+            Err(e) => {
+                event!(Level::ERROR, "Error - could not accept a connection: {}", e);
+                continue;
+            }
+        };
+
+        if let Err(e) = configure_stream(&stream, args) {
+            event!(Level::ERROR, "Error - could not configure the connection from {}: {:#}",
+                remote_address, e);
+        }
+
+        serve_connection(stream, router.clone(), remote_address.to_string(), args.http2);
+    }
+} // end serve
+
+/// This function accepts connections on a Unix domain socket and serves
+/// the router on each of them.  There are no socket options to configure.
+#[cfg(unix)]
+pub async fn serve_unix(
+    listener:   UnixListener,
+    router:     Router,
+    http2:      bool,
+) {
+    loop {
+        let (stream, remote_address) = match listener.accept().await {
+            Ok(connection) => connection,
+            Err(e) => {
+                event!(Level::ERROR, "Error - could not accept a Unix socket connection: {}", e);
+                continue;
+            }
+        };
+
+        serve_connection(stream, router.clone(), format!("{:?}", remote_address), http2);
+    }
+} // end serve_unix
+
+/// This function spawns a task serving the router on an accepted
+/// connection, so that multiple connections are served concurrently.
+/// Unless HTTP/2 is enabled, the connection is served as HTTP/1.1 only.
+/// With it, a client may instead start HTTP/2 with prior knowledge (h2c),
+/// though WebSocket upgrades still need HTTP/1.1.
+fn serve_connection<S>(
+    stream:         S,
+    router:         Router,
+    remote_address: String,
+    http2:          bool,
+)
+where
+    S: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+{
+    // Paths are normalized before the router matches them, so that /foo/
+    // is routed the same as /foo.
+    let service = TowerToHyperService::new(
+        NormalizePathLayer::trim_trailing_slash().layer(router));
+
+    tokio::spawn(async move {
+        // The auto builder can't be limited to HTTP/1.1 while supporting
+        // upgrades, so that case uses the HTTP/1.1 builder directly.
+        let result = if http2 {
+            auto::Builder::new(TokioExecutor::new())
+                .serve_connection_with_upgrades(TokioIo::new(stream), service)
+                .await
+        } else {
+            http1::Builder::new()
+                .serve_connection(TokioIo::new(stream), service)
+                .with_upgrades()
+                .await
+                .map_err(Into::into)
+        };
+
+        if let Err(e) = result {
+            event!(Level::DEBUG, "Error serving the connection from {}: {}", remote_address, e);
+        }
+    });
+} // end serve_connection
+
+#[cfg(test)]
+mod tests {
+    use clap::Parser;
+
+    use super::*;
+
+    /// This function accepts a loopback connection and applies the socket
+    /// options given on the command line to the server's end of it.
+    async fn configured_stream(options: &[&str]) -> TcpStream {
+        let args = Args::parse_from(std::iter::once("WebSocket-EchoServer").chain(options.iter().copied()));
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let _client = TcpStream::connect(listener.local_addr().unwrap()).await.unwrap();
+        let (stream, _) = listener.accept().await.unwrap();
+
+        configure_stream(&stream, &args).unwrap();
+        stream
+    }
+
+    #[tokio::test]
+    async fn nodelay_is_set_by_default() {
+        assert!(configured_stream(&[]).await.nodelay().unwrap());
+        assert!(!configured_stream(&["--tcp_nodelay", "false"]).await.nodelay().unwrap());
+    }
+
+    #[tokio::test]
+    async fn keepalive_is_set_when_configured() {
+        let stream = configured_stream(&[]).await;
+        assert!(!SockRef::from(&stream).keepalive().unwrap());
+
+        let stream = configured_stream(&["--tcp_keepalive_secs", "30"]).await;
+        let socket = SockRef::from(&stream);
+        assert!(socket.keepalive().unwrap());
+        assert_eq!(socket.tcp_keepalive_time().unwrap(), Duration::from_secs(30));
+        assert_eq!(socket.tcp_keepalive_interval().unwrap(), Duration::from_secs(30));
+    }
+}
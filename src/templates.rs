@@ -0,0 +1,115 @@
+use anyhow::Context;
+use notify::{
+    RecommendedWatcher,
+    RecursiveMode,
+    Watcher,
+};
+use serde::Deserialize;
+use std::{
+    path::{
+        Path,
+        PathBuf,
+    },
+    sync::{
+        Arc,
+        RwLock,
+    },
+};
+use tracing::{ event, Level };
+
+/// The MessageTemplate structure is one entry of a template file, giving
+/// the sender and text of a generated message.
+///
+/// `[{"sender":"Alice","text":"Hello"}, ...]`
+#[derive(Clone, Deserialize)]
+pub struct MessageTemplate {
+    pub sender: String,
+    pub text:   String,
+}
+
+/// The TemplateStore structure holds the templates most recently loaded
+/// from the template file.  The whole set is swapped at once on reload,
+/// so a generated message never mixes old and new content.
+pub struct TemplateStore {
+    path:       PathBuf,
+    templates:  RwLock<Arc<Vec<MessageTemplate>>>,
+}
+
+impl TemplateStore {
+    /// This function loads the template file at the given path.  The file
+    /// has to be valid at startup, since there is nothing to fall back on.
+    pub fn load(path: &Path) -> Result<TemplateStore, anyhow::Error> {
+        Ok(TemplateStore {
+            path:       path.to_path_buf(),
+            templates:  RwLock::new(Arc::new(read_templates(path)?)),
+        })
+    }
+
+    /// This method returns the current set of templates.
+    pub fn templates(&self) -> Arc<Vec<MessageTemplate>> {
+        self.templates.read().unwrap().clone()
+    }
+
+    /// This method reads the template file again, keeping the templates
+    /// we already have if the new content can't be used.
+    fn reload(&self) {
+        match read_templates(&self.path) {
+            Ok(templates) => {
+                event!(Level::INFO, "Reloaded {} message templates from {}",
+                    templates.len(), self.path.display());
+                *self.templates.write().unwrap() = Arc::new(templates);
+            }
+            Err(e) => {
+                event!(Level::ERROR, "Error - keeping the previous message templates: {:#}", e);
+            }
+        }
+    }
+} // end TemplateStore
+
+/// This function parses a template file, which must hold at least one
+/// template.
+fn read_templates(path: &Path) -> Result<Vec<MessageTemplate>, anyhow::Error> {
+    let contents = std::fs::read_to_string(path)
+        .with_context(|| format!("Unable to read the template file {}", path.display()))?;
+
+    let templates: Vec<MessageTemplate> = serde_json::from_str(&contents)
+        .with_context(|| format!("Unable to parse the template file {}", path.display()))?;
+
+    if templates.is_empty() {
+        anyhow::bail!("The template file {} holds no templates", path.display());
+    }
+
+    Ok(templates)
+} // end read_templates
+
+/// This function watches the store's template file, reloading it whenever
+/// it changes.  The watcher stops when the returned value is dropped.
+///
+/// We watch the file's directory rather than the file itself, because
+/// editors often save by replacing the file, which would end a watch on
+/// the original.
+pub fn watch(store: Arc<TemplateStore>) -> Result<RecommendedWatcher, anyhow::Error> {
+    let directory = match store.path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent.to_path_buf(),
+        _ => PathBuf::from("."),
+    };
+    let file_name = store.path.file_name().map(|name| name.to_os_string());
+
+    let watched_store = store.clone();
+    let mut watcher = notify::recommended_watcher(move |result: notify::Result<notify::Event>| {
+        match result {
+            Ok(event) if event.kind.is_create() || event.kind.is_modify() => {
+                if event.paths.iter().any(|path| path.file_name() == file_name.as_deref()) {
+                    watched_store.reload();
+                }
+            }
+            Ok(_) => {}
+            Err(e) => event!(Level::ERROR, "Error - could not watch the template file: {}", e),
+        }
+    }).context("Unable to create the template file watcher")?;
+
+    watcher.watch(&directory, RecursiveMode::NonRecursive)
+        .with_context(|| format!("Unable to watch {}", directory.display()))?;
+
+    Ok(watcher)
+} // end watch
@@ -0,0 +1,104 @@
+use chrono::{
+    DateTime,
+    NaiveDateTime,
+    SecondsFormat,
+    Utc,
+};
+use serde::{
+    de,
+    Deserialize,
+    Deserializer,
+    Serialize,
+    Serializer,
+};
+use std::fmt;
+
+/// The formats, besides RFC 3339 and RFC 2822, that a timestamp is accepted
+/// in.  They're all taken to be UTC.  The first is how the server wrote
+/// timestamps before they were RFC 3339, so that stored messages still load.
+const LEGACY_FORMATS: [&str; 3] = [
+    "%Y-%m-%d %H:%M:%S%.f UTC",
+    "%Y-%m-%dT%H:%M:%S%.f",
+    "%Y-%m-%d %H:%M:%S%.f",
+];
+
+/// The Timestamp structure is a point in time, always written in RFC 3339
+/// format, for example `2024-05-01T12:30:00.000Z`.  Reading one is more
+/// lenient; see Timestamp::parse.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Timestamp(pub DateTime<Utc>);
+
+impl Timestamp {
+    pub fn now() -> Timestamp {
+        Timestamp(Utc::now())
+    }
+
+    /// This function parses a timestamp in RFC 3339 or RFC 2822 format, or
+    /// in one of the legacy formats, returning a description of the problem
+    /// if it's in none of them.
+    pub fn parse(value: &str) -> Result<Timestamp, String> {
+        let value = value.trim();
+
+        if let Ok(date_time) = DateTime::parse_from_rfc3339(value) {
+            return Ok(Timestamp(date_time.with_timezone(&Utc)));
+        }
+
+        if let Ok(date_time) = DateTime::parse_from_rfc2822(value) {
+            return Ok(Timestamp(date_time.with_timezone(&Utc)));
+        }
+
+        LEGACY_FORMATS.iter()
+            .find_map(|format| NaiveDateTime::parse_from_str(value, format).ok())
+            .map(|date_time| Timestamp(date_time.and_utc()))
+            .ok_or_else(|| format!(
+                "invalid timestamp \"{}\", expected RFC 3339 such as 2024-05-01T12:30:00Z",
+                value))
+    }
+} // end Timestamp
+
+impl From<DateTime<Utc>> for Timestamp {
+    fn from(date_time: DateTime<Utc>) -> Self {
+        Timestamp(date_time)
+    }
+}
+
+impl std::ops::Add<chrono::Duration> for Timestamp {
+    type Output = Timestamp;
+
+    fn add(self, duration: chrono::Duration) -> Timestamp {
+        Timestamp(self.0 + duration)
+    }
+}
+
+impl fmt::Display for Timestamp {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.0.to_rfc3339_opts(SecondsFormat::Millis, true))
+    }
+}
+
+impl Serialize for Timestamp {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Timestamp {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value = String::deserialize(deserializer)?;
+        Timestamp::parse(&value).map_err(de::Error::custom)
+    }
+}
+
+/// This function deserializes an optional timestamp, treating an empty
+/// string the same as a missing one, since clients often send `""` for a
+/// bound they don't want.
+pub fn deserialize_optional<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<Timestamp>, D::Error> {
+    match Option::<String>::deserialize(deserializer)? {
+        Some(value) if !value.trim().is_empty() => {
+            Timestamp::parse(&value).map(Some).map_err(de::Error::custom)
+        }
+        _ => Ok(None),
+    }
+}
@@ -0,0 +1,91 @@
+use anyhow::Context;
+use base64::{
+    engine::general_purpose::STANDARD,
+    Engine,
+};
+use jsonwebtoken::{
+    Algorithm,
+    EncodingKey,
+    Header,
+};
+use rsa::{
+    pkcs1::{
+        DecodeRsaPrivateKey,
+        EncodeRsaPrivateKey,
+    },
+    pkcs8::{
+        DecodePrivateKey,
+        EncodePublicKey,
+    },
+    RsaPrivateKey,
+};
+use serde::Serialize;
+use std::path::Path;
+
+/// The size of the generated signing key, matching Keycloak's default.
+pub const TOKEN_KEY_BITS: usize = 2048;
+
+/// The TokenClaims structure holds the claims of an issued access token.
+#[derive(Serialize)]
+pub struct TokenClaims {
+    pub sub:    String,
+    pub realm:  String,
+    pub iat:    u64,
+    pub exp:    u64,
+}
+
+/// The TokenIssuer structure signs the access tokens handed out by the
+/// token route.  Its key pair is loaded from a file or generated when the
+/// server starts, and the public half is what the public key route serves,
+/// so that clients can verify the tokens they're issued.
+pub struct TokenIssuer {
+    encoding_key:   EncodingKey,
+
+    // The base64 encoding of the DER SubjectPublicKeyInfo, as Keycloak
+    // reports it.
+    public_key:     String,
+}
+
+impl TokenIssuer {
+    /// This function builds an issuer signing with the key in the given PEM
+    /// file, or with a newly generated key if there isn't one.
+    pub fn new(key_file: Option<&Path>) -> Result<TokenIssuer, anyhow::Error> {
+        let private_key = match key_file {
+            Some(path) => load_private_key(path)?,
+            None => RsaPrivateKey::new(&mut rand::thread_rng(), TOKEN_KEY_BITS)
+                .context("Unable to generate the token signing key")?,
+        };
+
+        let private_der = private_key.to_pkcs1_der()
+            .context("Unable to encode the token signing key")?;
+        let public_der = private_key.to_public_key().to_public_key_der()
+            .context("Unable to encode the token public key")?;
+
+        Ok(TokenIssuer {
+            encoding_key:   EncodingKey::from_rsa_der(private_der.as_bytes()),
+            public_key:     STANDARD.encode(public_der.as_bytes()),
+        })
+    }
+
+    /// This method returns the public key that verifies issued tokens.
+    pub fn public_key(&self) -> &str {
+        &self.public_key
+    }
+
+    /// This method returns an RS256 signed JWT carrying the given claims.
+    pub fn issue(&self, claims: &TokenClaims) -> String {
+        jsonwebtoken::encode(&Header::new(Algorithm::RS256), claims, &self.encoding_key)
+            .expect("Unable to sign the token")
+    }
+} // end TokenIssuer
+
+/// This function reads an RSA private key from a PEM file, in either the
+/// PKCS#8 form OpenSSL writes by default or the older PKCS#1 form.
+fn load_private_key(path: &Path) -> Result<RsaPrivateKey, anyhow::Error> {
+    let pem = std::fs::read_to_string(path)
+        .with_context(|| format!("Unable to read the token key file {}", path.display()))?;
+
+    RsaPrivateKey::from_pkcs8_pem(&pem)
+        .or_else(|_| RsaPrivateKey::from_pkcs1_pem(&pem))
+        .with_context(|| format!("{} doesn't hold a PEM encoded RSA private key", path.display()))
+} // end load_private_key
@@ -0,0 +1,22 @@
+/// Sender names drawn on when --unicode_sample is set.  Between them they
+/// cover CJK, right-to-left and emoji text.
+pub const UNICODE_SENDERS: [&str; 6] = [
+    "田中太郎",
+    "王小明",
+    "김민준",
+    "محمد",
+    "דוד",
+    "🦊 Fox",
+];
+
+/// Message texts drawn on when --unicode_sample is set.  Some of the emoji
+/// are made of several code points, so that clients counting characters
+/// have to count graphemes.
+pub const UNICODE_TEXTS: [&str; 6] = [
+    "これはテストメッセージです。",
+    "这是一条测试消息。",
+    "مرحبا، هذه رسالة اختبار.",
+    "שלום, זו הודעת בדיקה.",
+    "Family 👨‍👩‍👧‍👦 and flags 🇯🇵🇪🇬",
+    "Mixed direction: hello مرحبا שלום 世界 ✅",
+];
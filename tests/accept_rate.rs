@@ -0,0 +1,26 @@
+mod common;
+
+use common::*;
+use std::time::{
+    Duration,
+    Instant,
+};
+
+#[tokio::test]
+async fn connections_are_accepted_at_the_configured_rate() {
+    let server = TestServer::start_with_args(&["--accept_rate_per_sec", "20"]);
+
+    // Make each request on its own client, so that each needs its own
+    // connection.
+    let started = Instant::now();
+    let requests = (0..10).map(|_| {
+        let url = server.url(MESSAGES_ROUTE);
+        async move { reqwest::Client::new().get(url).send().await.unwrap() }
+    });
+    futures_util::future::join_all(requests).await;
+    let elapsed = started.elapsed();
+
+    // Ten connections at 20 a second take at least nine intervals of 50ms.
+    assert!(elapsed >= Duration::from_millis(400), "{:?}", elapsed);
+    assert!(elapsed < Duration::from_secs(5), "{:?}", elapsed);
+}
@@ -0,0 +1,37 @@
+mod common;
+
+use common::*;
+use reqwest::StatusCode;
+
+const TEST_ROUTE: &str = "/test";
+
+/// This function returns the status of a GET of the given path.
+async fn get_status(server: &TestServer, path: &str) -> StatusCode {
+    server.client().get(server.url(path)).send().await.unwrap().status()
+}
+
+#[tokio::test]
+async fn routes_are_served_under_the_base_path() {
+    let server = TestServer::start_with_args(&["--base_path", "/mock"]);
+
+    assert_eq!(get_status(&server, &format!("/mock{}", MESSAGES_ROUTE)).await, StatusCode::OK);
+    assert_eq!(get_status(&server, MESSAGES_ROUTE).await, StatusCode::NOT_FOUND);
+    assert_eq!(get_status(&server, TEST_ROUTE).await, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn health_checks_may_skip_the_base_path() {
+    let server = TestServer::start_with_args(&["--base_path", "/mock/", "--health_unprefixed"]);
+
+    assert_eq!(get_status(&server, &format!("/mock{}", TEST_ROUTE)).await, StatusCode::OK);
+    assert_eq!(get_status(&server, TEST_ROUTE).await, StatusCode::OK);
+    assert_eq!(get_status(&server, MESSAGES_ROUTE).await, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn websockets_are_served_under_the_base_path() {
+    let server = TestServer::start_with_args(&["--base_path", "/mock", "--ws_interval_ms", "50"]);
+
+    let mut stream = server.connect_ws(&format!("/mock{}", WS_SINGLE_ROOM_ROUTE)).await;
+    assert!(futures_util::StreamExt::next(&mut stream).await.is_some());
+}
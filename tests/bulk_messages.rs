@@ -0,0 +1,60 @@
+mod common;
+
+use common::*;
+use reqwest::StatusCode;
+
+const BULK_MESSAGES_ROUTE: &str = "/api/chatserver/messages/bulk";
+
+/// This function builds a send request for the test room with the given
+/// message text.
+fn send_request(message: &str) -> serde_json::Value {
+    serde_json::json!({
+        "classification":   "UNCLASSIFIED",
+        "domainId":         "chatsurferxmppunclass",
+        "message":          message,
+        "nickname":         "Tester",
+        "roomName":         "edge-view-test-room",
+    })
+}
+
+#[tokio::test]
+async fn valid_entries_are_accepted_despite_invalid_ones() {
+    let server = TestServer::start();
+
+    let request = serde_json::json!([
+        send_request("First of the batch"),
+        { "message": "Missing every other field" },
+        send_request("Last of the batch"),
+    ]);
+
+    let response = server.client().post(server.url(BULK_MESSAGES_ROUTE))
+        .body(request.to_string())
+        .send().await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body: serde_json::Value = serde_json::from_str(&response.text().await.unwrap()).unwrap();
+    assert_eq!(body["accepted"], 2);
+    assert_eq!(body["rejected"], 1);
+    assert_eq!(body["errors"].as_array().unwrap().len(), 1);
+    assert_eq!(body["errors"][0]["index"], 1);
+
+    // The accepted messages join the seeded ones.
+    let response = server.client().get(server.url(MESSAGES_ROUTE)).send().await.unwrap();
+    let body: serde_json::Value = serde_json::from_str(&response.text().await.unwrap()).unwrap();
+    let texts: Vec<&str> = body["messages"].as_array().unwrap().iter()
+        .map(|message| message["text"].as_str().unwrap())
+        .collect();
+    assert_eq!(texts.len(), 12);
+    assert!(texts.contains(&"First of the batch"));
+    assert!(texts.contains(&"Last of the batch"));
+}
+
+#[tokio::test]
+async fn requests_that_are_not_arrays_are_rejected() {
+    let server = TestServer::start();
+
+    let response = server.client().post(server.url(BULK_MESSAGES_ROUTE))
+        .body(send_request("Not in an array").to_string())
+        .send().await.unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
@@ -0,0 +1,50 @@
+mod common;
+
+use common::*;
+use reqwest::StatusCode;
+use std::time::{
+    Duration,
+    Instant,
+};
+
+const BUSY_ROUTE: &str = "/api/test/busy";
+
+/// This function asks the server to spin for 200ms, returning the
+/// response body and how long the request took.
+async fn spin(server: &TestServer, offload: bool) -> (serde_json::Value, Duration) {
+    let started = Instant::now();
+
+    let response = server.client()
+        .get(server.url(&format!("{}?ms=200&offload={}", BUSY_ROUTE, offload)))
+        .send().await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body = serde_json::from_str(&response.text().await.unwrap()).unwrap();
+    (body, started.elapsed())
+}
+
+#[tokio::test]
+async fn the_handler_spins_for_the_duration() {
+    let server = TestServer::start();
+
+    let (body, elapsed) = spin(&server, false).await;
+    assert_eq!(body, serde_json::json!({ "ms": 200, "offloaded": false }));
+    assert!(elapsed >= Duration::from_millis(200), "{:?}", elapsed);
+}
+
+#[tokio::test]
+async fn the_work_can_be_offloaded() {
+    let server = TestServer::start();
+
+    let (body, elapsed) = spin(&server, true).await;
+    assert_eq!(body, serde_json::json!({ "ms": 200, "offloaded": true }));
+    assert!(elapsed >= Duration::from_millis(200), "{:?}", elapsed);
+}
+
+#[tokio::test]
+async fn the_duration_is_required() {
+    let server = TestServer::start();
+
+    let response = server.client().get(server.url(BUSY_ROUTE)).send().await.unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
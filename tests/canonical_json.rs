@@ -0,0 +1,84 @@
+mod common;
+
+use common::*;
+use serde::de::{
+    Deserialize,
+    Deserializer,
+    MapAccess,
+    SeqAccess,
+    Visitor,
+};
+use std::fmt;
+
+/// This structure deserializes any JSON document, recording whether the
+/// keys of every object in it are in sorted order.
+struct SortedKeys(bool);
+
+impl<'de> Deserialize<'de> for SortedKeys {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(SortedKeysVisitor)
+    }
+}
+
+struct SortedKeysVisitor;
+
+impl<'de> Visitor<'de> for SortedKeysVisitor {
+    type Value = SortedKeys;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("any JSON value")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<SortedKeys, A::Error> {
+        let mut sorted = true;
+        let mut previous: Option<String> = None;
+
+        while let Some(key) = map.next_key::<String>()? {
+            sorted &= previous.is_none_or(|previous| previous < key);
+            sorted &= map.next_value::<SortedKeys>()?.0;
+            previous = Some(key);
+        }
+
+        Ok(SortedKeys(sorted))
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<SortedKeys, A::Error> {
+        let mut sorted = true;
+
+        while let Some(element) = seq.next_element::<SortedKeys>()? {
+            sorted &= element.0;
+        }
+
+        Ok(SortedKeys(sorted))
+    }
+
+    fn visit_bool<E>(self, _: bool) -> Result<SortedKeys, E> { Ok(SortedKeys(true)) }
+    fn visit_i64<E>(self, _: i64) -> Result<SortedKeys, E> { Ok(SortedKeys(true)) }
+    fn visit_u64<E>(self, _: u64) -> Result<SortedKeys, E> { Ok(SortedKeys(true)) }
+    fn visit_f64<E>(self, _: f64) -> Result<SortedKeys, E> { Ok(SortedKeys(true)) }
+    fn visit_str<E>(self, _: &str) -> Result<SortedKeys, E> { Ok(SortedKeys(true)) }
+    fn visit_unit<E>(self) -> Result<SortedKeys, E> { Ok(SortedKeys(true)) }
+}
+
+/// This function fetches the test room's messages, returning whether the
+/// response's keys were sorted.
+async fn messages_keys_sorted(server: &TestServer) -> bool {
+    let response = server.client().get(server.url(MESSAGES_ROUTE)).send().await.unwrap();
+    let body = response.text().await.unwrap();
+
+    serde_json::from_str::<SortedKeys>(&body).unwrap().0
+}
+
+#[tokio::test]
+async fn canonical_responses_have_sorted_keys() {
+    let server = TestServer::start_with_args(&["--canonical_json"]);
+
+    assert!(messages_keys_sorted(&server).await);
+}
+
+#[tokio::test]
+async fn responses_are_in_declaration_order_by_default() {
+    let server = TestServer::start();
+
+    assert!(!messages_keys_sorted(&server).await);
+}
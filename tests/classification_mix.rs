@@ -0,0 +1,71 @@
+mod common;
+
+use common::*;
+
+const SLOW_BODY_ROUTE: &str = "/api/test/slow_body";
+
+/// The classifications the servers mix, from lowest to highest.
+const LEVELS: [&str; 3] = ["UNCLASSIFIED", "CONFIDENTIAL", "SECRET"];
+
+/// This function starts a server whose messages have a mix of
+/// classifications.
+fn start_mixed_server() -> TestServer {
+    TestServer::start_with_args(&[
+        "--classification_mix", "UNCLASSIFIED,CONFIDENTIAL,SECRET",
+        "--empty_query_behavior", "all",
+        "--seed", "7",
+    ])
+}
+
+/// This function checks that the response's classification is the
+/// highest of its messages', which have to be mixed for the check to
+/// mean anything.
+fn assert_highest_classification(body: &serde_json::Value) {
+    let ranks: Vec<usize> = body["messages"].as_array().unwrap().iter()
+        .map(|message| LEVELS.iter().position(|level| message["classification"] == *level).unwrap())
+        .collect();
+
+    let highest = *ranks.iter().max().unwrap();
+    assert!(ranks.iter().any(|&rank| rank != highest), "{:?}", ranks);
+
+    assert_eq!(body["classification"], LEVELS[highest]);
+}
+
+#[tokio::test]
+async fn the_response_reflects_the_highest_message_classification() {
+    let server = start_mixed_server();
+
+    let response = server.client().get(server.url(MESSAGES_ROUTE))
+        .send().await.unwrap();
+    let body: serde_json::Value = serde_json::from_str(&response.text().await.unwrap()).unwrap();
+
+    assert_highest_classification(&body);
+}
+
+#[tokio::test]
+async fn search_results_reflect_the_highest_message_classification() {
+    let server = start_mixed_server();
+
+    let request = serde_json::json!({
+        "keywordFilter":            { "query": "" },
+        "UserHighClassification":   "SECRET",
+    });
+
+    let response = server.client().post(server.url(SEARCH_MESSAGES_ROUTE))
+        .body(request.to_string())
+        .send().await.unwrap();
+    let body: serde_json::Value = serde_json::from_str(&response.text().await.unwrap()).unwrap();
+
+    assert_highest_classification(&body);
+}
+
+#[tokio::test]
+async fn slow_bodies_reflect_the_highest_message_classification() {
+    let server = start_mixed_server();
+
+    let response = server.client().get(server.url(&format!("{}?chunk_delay_ms=0", SLOW_BODY_ROUTE)))
+        .send().await.unwrap();
+    let body: serde_json::Value = serde_json::from_str(&response.text().await.unwrap()).unwrap();
+
+    assert_highest_classification(&body);
+}
@@ -0,0 +1,25 @@
+mod common;
+
+use common::*;
+use reqwest::StatusCode;
+
+const CLASSIFICATIONS_ROUTE: &str = "/api/chat/classifications";
+
+#[tokio::test]
+async fn classifications_are_listed_in_rank_order() {
+    let server = TestServer::start();
+
+    let response = server.client().get(server.url(CLASSIFICATIONS_ROUTE)).send().await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body: serde_json::Value = serde_json::from_str(&response.text().await.unwrap()).unwrap();
+    let classifications = body.as_array().unwrap();
+
+    let rank = |name: &str| classifications.iter()
+        .find(|classification| classification["name"] == name)
+        .map(|classification| classification["rank"].as_u64().unwrap())
+        .unwrap();
+    assert_eq!(rank("UNCLASSIFIED"), 0);
+    assert!(rank("UNCLASSIFIED") < rank("SECRET"));
+    assert!(rank("SECRET") < rank("TOP SECRET"));
+}
@@ -0,0 +1,55 @@
+mod common;
+
+use common::*;
+use reqwest::StatusCode;
+
+/// This function searches as a user with the given clearance.
+async fn search_as(server: &TestServer, clearance: &str) -> reqwest::Response {
+    let request = serde_json::json!({
+        "keywordFilter":            { "query": "test" },
+        "UserHighClassification":   clearance,
+    });
+
+    server.client().post(server.url(SEARCH_MESSAGES_ROUTE))
+        .body(request.to_string())
+        .send().await.unwrap()
+}
+
+#[tokio::test]
+async fn users_cleared_below_the_data_are_forbidden() {
+    let server = TestServer::start_with_args(&["--classification", "SECRET"]);
+
+    let response = search_as(&server, "UNCLASSIFIED").await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+    let body: serde_json::Value = serde_json::from_str(&response.text().await.unwrap()).unwrap();
+    assert_eq!(body["code"], 403);
+    assert_eq!(body["classification"], "UNCLASSIFIED");
+    assert_eq!(
+        body["message"],
+        "The user's classification UNCLASSIFIED is lower than the data's classification SECRET.");
+}
+
+#[tokio::test]
+async fn users_cleared_for_the_data_can_search() {
+    let server = TestServer::start_with_args(&["--classification", "SECRET"]);
+
+    let response = search_as(&server, "SECRET").await;
+    assert_eq!(response.status(), StatusCode::OK);
+}
+
+#[tokio::test]
+async fn the_clearance_covers_the_classification_mix() {
+    let server = TestServer::start_with_args(&["--classification_mix", "UNCLASSIFIED,SECRET"]);
+
+    let response = search_as(&server, "CONFIDENTIAL").await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+    let body: serde_json::Value = serde_json::from_str(&response.text().await.unwrap()).unwrap();
+    assert_eq!(
+        body["message"],
+        "The user's classification CONFIDENTIAL is lower than the data's classification SECRET.");
+
+    let response = search_as(&server, "SECRET").await;
+    assert_eq!(response.status(), StatusCode::OK);
+}
@@ -0,0 +1,58 @@
+mod common;
+
+use chrono::{
+    DateTime,
+    Utc,
+};
+use common::*;
+
+/// The skew the servers are started with, a day either way.
+const SKEW_SECS: i64 = 86400;
+
+/// This function returns the timestamps of the seeded messages.
+async fn timestamps(server: &TestServer) -> Vec<DateTime<Utc>> {
+    let response = server.client().get(server.url(MESSAGES_ROUTE)).send().await.unwrap();
+    let body: serde_json::Value = serde_json::from_str(&response.text().await.unwrap()).unwrap();
+
+    body["messages"].as_array().unwrap().iter()
+        .map(|message| message["timestamp"].as_str().unwrap().parse().unwrap())
+        .collect()
+}
+
+#[tokio::test]
+async fn timestamps_are_skewed_within_the_bound() {
+    let server = TestServer::start_with_args(&["--clock_skew_secs", "86400", "--seed", "7"]);
+    let now = Utc::now();
+
+    let offsets: Vec<i64> = timestamps(&server).await.iter()
+        .map(|timestamp| (*timestamp - now).num_seconds())
+        .collect();
+
+    // Allow a minute for the time the server took to start.
+    assert!(offsets.iter().all(|offset| offset.abs() <= SKEW_SECS + 60), "{:?}", offsets);
+    assert!(offsets.iter().any(|offset| offset.abs() > 60), "{:?}", offsets);
+}
+
+#[tokio::test]
+async fn skew_is_reproducible_under_a_seed() {
+    let args = ["--clock_skew_secs", "86400", "--seed", "7"];
+    let first = timestamps(&TestServer::start_with_args(&args)).await;
+    let second = timestamps(&TestServer::start_with_args(&args)).await;
+
+    // The servers started at different times, but skewed each message
+    // by the same amount.
+    assert_eq!(first.len(), second.len());
+    for (first, second) in first.iter().zip(&second) {
+        assert!((*second - *first).num_seconds().abs() <= 5, "{} {}", first, second);
+    }
+}
+
+#[tokio::test]
+async fn timestamps_are_not_skewed_by_default() {
+    let server = TestServer::start();
+    let now = Utc::now();
+
+    for timestamp in timestamps(&server).await {
+        assert!((timestamp - now).num_seconds().abs() <= 60, "{}", timestamp);
+    }
+}
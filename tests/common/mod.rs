@@ -0,0 +1,135 @@
+// Not every test file uses every helper.
+#![allow(dead_code)]
+
+use std::{
+    io::{
+        BufRead,
+        BufReader,
+    },
+    net::SocketAddr,
+    process::{
+        Child,
+        Command,
+        Stdio,
+    },
+    sync::mpsc,
+    thread,
+    time::Duration,
+};
+use tokio::net::TcpStream;
+use tokio_tungstenite::{
+    MaybeTlsStream,
+    WebSocketStream,
+};
+
+pub const MESSAGES_ROUTE: &str = "/api/chat/messages/chatsurferxmppunclass/edge-view-test-room";
+pub const GET_API_KEY_ROUTE: &str = "/api/auth/key";
+pub const NEW_MESSAGE_ROUTE: &str = "/api/chatserver/message";
+pub const SEARCH_MESSAGES_ROUTE: &str = "/api/chat/messages/search";
+pub const WS_SINGLE_ROOM_ROUTE: &str = "/topic/chat-messages-room/chatsurferxmppunclass/edge-view-test-room";
+
+/// The line the server logs once it is accepting connections.
+const LISTENING_MARKER: &str = "Listening on ";
+
+/// How long to wait for the server to start before giving up.
+const STARTUP_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// The interval between generated WebSocket messages unless a test picks
+/// its own, so that tests waiting on a stream don't wait long.
+const WS_INTERVAL_MS: &str = "50";
+
+/// The token signing key servers are given unless a test picks its own,
+/// which spares each of them generating one.
+pub const TOKEN_KEY_FILE: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/token_key.pem");
+
+/// The TestServer structure runs the server binary on a free port for the
+/// length of a test, and kills it when dropped.
+pub struct TestServer {
+    pub address:    SocketAddr,
+    child:          Child,
+}
+
+impl TestServer {
+    /// This function starts a server with the default arguments.
+    pub fn start() -> TestServer {
+        TestServer::start_with_args(&[])
+    }
+
+    /// This function starts a server with the given extra arguments, and
+    /// waits until it reports the address it is listening on.
+    pub fn start_with_args(args: &[&str]) -> TestServer {
+        let mut command = Command::new(env!("CARGO_BIN_EXE_WebSocket-EchoServer"));
+        command.args(["--client_serve_ip", "127.0.0.1", "--client_port", "0"]);
+
+        if !args.contains(&"--token_key_file") {
+            command.args(["--token_key_file", TOKEN_KEY_FILE]);
+        }
+
+        if !args.contains(&"--ws_interval_ms") {
+            command.args(["--ws_interval_ms", WS_INTERVAL_MS]);
+        }
+
+        let mut child = command
+            .args(args)
+            .env("NO_COLOR", "1")
+            .stdout(Stdio::piped())
+            .stderr(Stdio::inherit())
+            .spawn()
+            .expect("Unable to start the server");
+
+        // Read the log on another thread, so that the server never blocks
+        // on a full pipe and we can time out waiting for it.
+        let stdout = child.stdout.take().unwrap();
+        let (sender, receiver) = mpsc::channel();
+
+        thread::spawn(move || {
+            for line in BufReader::new(stdout).lines().map_while(Result::ok) {
+                if let Some((_, address)) = line.split_once(LISTENING_MARKER) {
+                    let _ = sender.send(address.trim().to_string());
+                }
+            }
+        });
+
+        let address = match receiver.recv_timeout(STARTUP_TIMEOUT) {
+            Ok(address) => address.parse().expect("Unable to parse the server address"),
+            Err(e) => {
+                let _ = child.kill();
+                panic!("The server didn't start listening: {}", e);
+            }
+        };
+
+        TestServer {
+            address,
+            child,
+        }
+    }
+
+    /// This method returns the HTTP URL of the given route.
+    pub fn url(&self, route: &str) -> String {
+        format!("http://{}{}", self.address, route)
+    }
+
+    /// This method returns a client for making HTTP requests.
+    pub fn client(&self) -> reqwest::Client {
+        reqwest::Client::new()
+    }
+
+    /// This method opens a WebSocket connection to the given route, which
+    /// may include a query string.
+    pub async fn connect_ws(&self, route: &str)
+        -> WebSocketStream<MaybeTlsStream<TcpStream>> {
+        let (stream, _) = tokio_tungstenite::connect_async(
+            format!("ws://{}{}", self.address, route))
+            .await
+            .expect("Unable to open the WebSocket connection");
+
+        stream
+    }
+} // end TestServer
+
+impl Drop for TestServer {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
@@ -0,0 +1,63 @@
+mod common;
+
+use common::*;
+use flate2::{
+    write::ZlibEncoder,
+    Compression,
+};
+use reqwest::StatusCode;
+use std::io::Write;
+
+/// A search request for every message, gzip compressed:
+/// `{"keywordFilter":{"query":""},"userHighClassification":"UNCLASSIFIED"}`
+const GZIPPED_SEARCH_REQUEST: &str = "1f8b0800000000000203ab56ca4ead2ccf2f4a71cbcc29492d52b2aa562a2c4d2daa54b25252aad5512a2d4e2df2c84ccf70ce492c2ece4ccb4c4e2cc9cccf034a86fa39fb3806077bba79baba28d50200295864af46000000";
+
+/// This function posts the given body to the search route, with the given
+/// Content-Encoding.
+async fn search_encoded(server: &TestServer, encoding: &str, body: Vec<u8>) -> reqwest::Response {
+    server.client().post(server.url(SEARCH_MESSAGES_ROUTE))
+        .header("Content-Encoding", encoding)
+        .body(body)
+        .send().await.unwrap()
+}
+
+#[tokio::test]
+async fn gzipped_search_requests_are_decompressed() {
+    let server = TestServer::start_with_args(&["--empty_query_behavior", "all"]);
+
+    let response = search_encoded(&server, "gzip", hex::decode(GZIPPED_SEARCH_REQUEST).unwrap()).await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body: serde_json::Value = serde_json::from_str(&response.text().await.unwrap()).unwrap();
+    assert_eq!(body["total"], 10);
+}
+
+#[tokio::test]
+async fn deflated_search_requests_are_decompressed() {
+    let server = TestServer::start_with_args(&["--empty_query_behavior", "all"]);
+
+    let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(br#"{"keywordFilter":{"query":""},"userHighClassification":"UNCLASSIFIED"}"#).unwrap();
+
+    let response = search_encoded(&server, "deflate", encoder.finish().unwrap()).await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body: serde_json::Value = serde_json::from_str(&response.text().await.unwrap()).unwrap();
+    assert_eq!(body["total"], 10);
+}
+
+#[tokio::test]
+async fn corrupt_compressed_bodies_are_refused() {
+    let server = TestServer::start();
+
+    let response = search_encoded(&server, "gzip", b"not gzip".to_vec()).await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn unsupported_encodings_are_refused() {
+    let server = TestServer::start();
+
+    let response = search_encoded(&server, "br", hex::decode(GZIPPED_SEARCH_REQUEST).unwrap()).await;
+    assert_eq!(response.status(), StatusCode::UNSUPPORTED_MEDIA_TYPE);
+}
@@ -0,0 +1,42 @@
+mod common;
+
+use common::*;
+use reqwest::{
+    header::{
+        ACCEPT,
+        CONTENT_TYPE,
+    },
+    StatusCode,
+};
+
+#[tokio::test]
+async fn get_messages_returns_xml_when_asked() {
+    let server = TestServer::start();
+
+    let response = server.client().get(server.url(MESSAGES_ROUTE))
+        .header(ACCEPT, "application/xml")
+        .send().await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()[CONTENT_TYPE], "application/xml");
+
+    let body = response.text().await.unwrap();
+    assert!(body.starts_with("<GetChatMessagesResponse>"));
+    assert!(body.ends_with("</GetChatMessagesResponse>"));
+    assert_eq!(body.matches("<messages>").count(), 10);
+    assert_eq!(body.matches("</messages>").count(), 10);
+}
+
+#[tokio::test]
+async fn get_messages_defaults_to_json() {
+    let server = TestServer::start();
+
+    for accept in [None, Some("application/json")] {
+        let mut request = server.client().get(server.url(MESSAGES_ROUTE));
+        if let Some(accept) = accept {
+            request = request.header(ACCEPT, accept);
+        }
+
+        let body = request.send().await.unwrap().text().await.unwrap();
+        assert!(serde_json::from_str::<serde_json::Value>(&body).is_ok());
+    }
+}
@@ -0,0 +1,92 @@
+mod common;
+
+use chrono::{
+    DateTime,
+    Utc,
+};
+use common::*;
+use futures_util::StreamExt;
+use std::{
+    collections::HashSet,
+    time::Duration,
+};
+use tokio_tungstenite::tungstenite::Message;
+
+/// The number of messages in a cycle of the demo script.
+const CYCLE_LENGTH: usize = 6;
+
+#[tokio::test]
+async fn the_demo_stream_cycles_through_its_script() {
+    let server = TestServer::start_with_args(&["--demo_mode"]);
+    let mut stream = server.connect_ws(WS_SINGLE_ROOM_ROUTE).await;
+
+    let mut messages: Vec<serde_json::Value> = Vec::new();
+
+    while messages.len() < 2 * CYCLE_LENGTH {
+        let message = tokio::time::timeout(Duration::from_secs(5), stream.next())
+            .await
+            .expect("No message arrived")
+            .unwrap()
+            .unwrap();
+
+        if let Message::Text(text) = message {
+            let body: serde_json::Value = serde_json::from_str(&text).unwrap();
+
+            if body["type"] != "resume" {
+                messages.push(body);
+            }
+        }
+    }
+
+    // Each cycle repeats the last, apart from the timestamps.
+    for (first, second) in messages.iter().zip(&messages[CYCLE_LENGTH..]) {
+        for field in ["id", "sender", "roomName", "text"] {
+            assert_eq!(first[field], second[field]);
+        }
+    }
+
+    // A cycle covers distinct senders and several rooms.
+    let senders: HashSet<&str> = messages[..CYCLE_LENGTH].iter()
+        .map(|message| message["sender"].as_str().unwrap())
+        .collect();
+    let rooms: HashSet<&str> = messages[..CYCLE_LENGTH].iter()
+        .map(|message| message["roomName"].as_str().unwrap())
+        .collect();
+    assert_eq!(senders.len(), CYCLE_LENGTH);
+    assert!(rooms.len() > 1);
+
+    // The timestamps advance a second per message.
+    let timestamps: Vec<DateTime<Utc>> = messages.iter()
+        .map(|message| message["timestamp"].as_str().unwrap().parse().unwrap())
+        .collect();
+    assert!(timestamps.windows(2).all(|pair| (pair[1] - pair[0]).num_seconds() == 1),
+        "{:?}", timestamps);
+}
+
+#[tokio::test]
+async fn demo_messages_are_counted() {
+    let server = TestServer::start_with_args(&["--demo_mode", "--ws_interval_ms", "50"]);
+    let mut stream = server.connect_ws(WS_SINGLE_ROOM_ROUTE).await;
+
+    let mut received = 0;
+
+    while received < CYCLE_LENGTH {
+        let message = tokio::time::timeout(Duration::from_secs(5), stream.next())
+            .await
+            .expect("No message arrived")
+            .unwrap()
+            .unwrap();
+
+        if let Message::Text(text) = message {
+            let body: serde_json::Value = serde_json::from_str(&text).unwrap();
+
+            if body["type"] != "resume" {
+                received += 1;
+            }
+        }
+    }
+
+    let response = server.client().get(server.url("/api/test/state")).send().await.unwrap();
+    let state: serde_json::Value = serde_json::from_str(&response.text().await.unwrap()).unwrap();
+    assert!(state["messages_generated"].as_u64().unwrap() >= CYCLE_LENGTH as u64);
+}
@@ -0,0 +1,26 @@
+mod common;
+
+use common::*;
+use reqwest::StatusCode;
+
+const DOCS_ROUTE: &str = "/docs";
+const OPENAPI_ROUTE: &str = "/openapi.json";
+
+#[tokio::test]
+async fn the_docs_page_loads_the_openapi_document() {
+    let server = TestServer::start();
+
+    let response = server.client().get(server.url(DOCS_ROUTE)).send().await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert!(response.headers()["content-type"].to_str().unwrap().starts_with("text/html"));
+
+    // The page fetches the document relative to itself.
+    let page = response.text().await.unwrap();
+    assert!(page.contains("fetch(\"openapi.json\")"));
+
+    let response = server.client().get(server.url(OPENAPI_ROUTE)).send().await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let document: serde_json::Value = serde_json::from_str(&response.text().await.unwrap()).unwrap();
+    assert!(document["paths"][DOCS_ROUTE].is_object());
+}
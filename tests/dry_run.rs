@@ -0,0 +1,66 @@
+use std::process::{
+    Command,
+    ExitStatus,
+};
+
+/// This function runs the server in dry run mode with the given extra
+/// arguments, returning how it exited.
+fn dry_run(args: &[&str]) -> ExitStatus {
+    Command::new(env!("CARGO_BIN_EXE_WebSocket-EchoServer"))
+        .args(["--dry_run", "--client_serve_ip", "127.0.0.1"])
+        .args(args)
+        .output()
+        .expect("Unable to run the server")
+        .status
+}
+
+#[test]
+fn a_valid_configuration_passes() {
+    assert!(dry_run(&["--client_port", "0"]).success());
+}
+
+#[test]
+fn a_port_in_use_fails() {
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port().to_string();
+
+    assert!(!dry_run(&["--client_port", &port]).success());
+}
+
+#[test]
+fn an_unreadable_template_file_fails() {
+    assert!(!dry_run(&["--client_port", "0", "--template_file", "/nonexistent/templates.json"]).success());
+}
+
+#[test]
+fn a_missing_token_key_file_fails() {
+    assert!(!dry_run(&["--client_port", "0", "--token_key_file", "/nonexistent/token_key.pem"]).success());
+}
+
+#[test]
+fn an_unwritable_capture_file_fails() {
+    assert!(!dry_run(&["--client_port", "0", "--ws_capture_file", "/nonexistent/capture.jsonl"]).success());
+}
+
+#[test]
+fn a_writable_capture_file_passes_without_being_created() {
+    let path = std::env::temp_dir().join(format!("dry_run_capture_{}.jsonl", std::process::id()));
+
+    assert!(dry_run(&["--client_port", "0", "--ws_capture_file", path.to_str().unwrap()]).success());
+    assert!(!path.exists());
+}
+
+#[cfg(unix)]
+#[test]
+fn a_unix_socket_in_a_missing_directory_fails() {
+    assert!(!dry_run(&["--client_port", "0", "--uds_path", "/nonexistent/server.sock"]).success());
+}
+
+#[cfg(unix)]
+#[test]
+fn a_bindable_unix_socket_passes_and_is_removed() {
+    let path = std::env::temp_dir().join(format!("dry_run_{}.sock", std::process::id()));
+
+    assert!(dry_run(&["--client_port", "0", "--uds_path", path.to_str().unwrap()]).success());
+    assert!(!path.exists());
+}
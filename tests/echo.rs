@@ -0,0 +1,39 @@
+mod common;
+
+use common::*;
+use reqwest::StatusCode;
+
+const ECHO_ROUTE: &str = "/api/test/echo";
+
+#[tokio::test]
+async fn bodies_are_echoed_verbatim() {
+    let server = TestServer::start();
+
+    // The body needn't be valid JSON.
+    let sent = "{\"message\": \"unterminated";
+
+    let response = server.client().post(server.url(ECHO_ROUTE))
+        .header("Content-Type", "application/json; charset=utf-8")
+        .body(sent)
+        .send().await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body: serde_json::Value = serde_json::from_str(&response.text().await.unwrap()).unwrap();
+    assert_eq!(body["body"], sent);
+    assert_eq!(body["bytes"], sent.len());
+    assert_eq!(body["content_type"], "application/json; charset=utf-8");
+}
+
+#[tokio::test]
+async fn a_missing_content_type_is_null() {
+    let server = TestServer::start();
+
+    let response = server.client().post(server.url(ECHO_ROUTE))
+        .body("héllo")
+        .send().await.unwrap();
+
+    let body: serde_json::Value = serde_json::from_str(&response.text().await.unwrap()).unwrap();
+    assert_eq!(body["body"], "héllo");
+    assert_eq!(body["bytes"], 6);
+    assert!(body["content_type"].is_null());
+}
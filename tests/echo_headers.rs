@@ -0,0 +1,76 @@
+mod common;
+
+use common::*;
+use reqwest::StatusCode;
+
+#[tokio::test]
+async fn prefixed_headers_are_echoed_without_the_prefix() {
+    let server = TestServer::start();
+
+    let response = server.client().get(server.url(MESSAGES_ROUTE))
+        .header("x-echo-foo", "bar")
+        .header("X-Echo-Retry-After", "5")
+        .header("x-other", "ignored")
+        .send().await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    assert_eq!(response.headers()["foo"], "bar");
+    assert_eq!(response.headers()["retry-after"], "5");
+    assert!(response.headers().get("x-other").is_none());
+    assert!(response.headers().get("other").is_none());
+}
+
+#[tokio::test]
+async fn headers_are_echoed_on_unknown_routes_too() {
+    let server = TestServer::start();
+
+    let response = server.client().get(server.url("/no/such/route"))
+        .header("x-echo-foo", "bar")
+        .send().await.unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    assert_eq!(response.headers()["foo"], "bar");
+}
+
+#[tokio::test]
+async fn framing_headers_are_not_echoed() {
+    let server = TestServer::start();
+
+    let response = server.client().get(server.url(MESSAGES_ROUTE))
+        .header("x-echo-content-length", "0")
+        .header("x-echo-transfer-encoding", "chunked")
+        .header("x-echo-connection", "close")
+        .header("x-echo-foo", "bar")
+        .send().await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    assert_eq!(response.headers()["foo"], "bar");
+    assert!(response.headers().get("transfer-encoding").is_none());
+    assert_eq!(response.headers().get_all("content-length").iter().count(), 1);
+
+    // The body still arrives whole, rather than being cut off at zero
+    // bytes.
+    let length: usize = response.headers()["content-length"].to_str().unwrap().parse().unwrap();
+    let body = response.text().await.unwrap();
+    assert!(length > 0);
+    assert_eq!(body.len(), length);
+    serde_json::from_str::<serde_json::Value>(&body).unwrap();
+}
+
+#[tokio::test]
+async fn body_headers_are_not_echoed() {
+    let server = TestServer::start();
+
+    let response = server.client().get(server.url(MESSAGES_ROUTE))
+        .header("x-echo-content-type", "image/png")
+        .header("x-echo-content-encoding", "gzip")
+        .send().await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    // Only the server's own content type is sent.
+    let content_types: Vec<_> = response.headers().get_all("content-type").iter().collect();
+    assert_eq!(content_types.len(), 1);
+    assert_ne!(content_types[0], "image/png");
+    assert!(response.headers().get("content-encoding").is_none());
+
+    serde_json::from_str::<serde_json::Value>(&response.text().await.unwrap()).unwrap();
+}
@@ -0,0 +1,53 @@
+mod common;
+
+use common::*;
+use reqwest::StatusCode;
+use sha2::Digest;
+
+#[tokio::test]
+async fn matching_etags_get_not_modified() {
+    let server = TestServer::start();
+
+    let response = server.client().get(server.url(MESSAGES_ROUTE))
+        .send().await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let etag = response.headers()["etag"].to_str().unwrap().to_string();
+
+    let response = server.client().get(server.url(MESSAGES_ROUTE))
+        .header("If-None-Match", &etag)
+        .send().await.unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_MODIFIED);
+    assert_eq!(response.headers()["etag"].to_str().unwrap(), etag);
+    assert!(response.text().await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn stale_etags_get_the_full_body() {
+    let server = TestServer::start();
+
+    let response = server.client().get(server.url(MESSAGES_ROUTE))
+        .header("If-None-Match", "\"stale\"")
+        .send().await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert!(!response.text().await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn etags_describe_the_canonical_body() {
+    let server = TestServer::start_with_args(&["--canonical_json"]);
+
+    let response = server.client().get(server.url(MESSAGES_ROUTE))
+        .send().await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let etag = response.headers()["etag"].to_str().unwrap().to_string();
+
+    // The ETag is a truncated SHA-256 of the bytes received.
+    let body = response.bytes().await.unwrap();
+    let digest = sha2::Sha256::digest(&body);
+    assert_eq!(etag, format!("\"{}\"", hex::encode(&digest[..16])));
+
+    let response = server.client().get(server.url(MESSAGES_ROUTE))
+        .header("If-None-Match", &etag)
+        .send().await.unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_MODIFIED);
+}
@@ -0,0 +1,51 @@
+mod common;
+
+use common::*;
+
+/// This function returns the sorted field names of every geo-tag in the
+/// response body.
+fn geo_tag_fields(body: &str) -> Vec<Vec<String>> {
+    let body: serde_json::Value = serde_json::from_str(body).unwrap();
+
+    body["messages"].as_array().unwrap().iter()
+        .filter_map(|message| message["geoTags"].as_array())
+        .flatten()
+        .map(|geo_tag| {
+            let mut fields: Vec<String> = geo_tag.as_object().unwrap().keys().cloned().collect();
+            fields.sort();
+            fields
+        })
+        .collect()
+}
+
+#[tokio::test]
+async fn get_returns_only_the_selected_geo_tag_fields() {
+    let server = TestServer::start();
+
+    let response = server.client()
+        .get(server.url(&format!("{}?geo_fields=location,regions,unknown", MESSAGES_ROUTE)))
+        .send().await.unwrap();
+    let geo_tags = geo_tag_fields(&response.text().await.unwrap());
+
+    assert!(!geo_tags.is_empty());
+    assert!(geo_tags.iter().all(|fields| fields == &["location", "regions"]));
+}
+
+#[tokio::test]
+async fn search_returns_only_the_selected_geo_tag_fields() {
+    let server = TestServer::start_with_args(&["--empty_query_behavior", "all"]);
+
+    let request = serde_json::json!({
+        "keywordFilter":            { "query": "" },
+        "UserHighClassification":   "UNCLASSIFIED",
+    });
+
+    let response = server.client()
+        .post(server.url(&format!("{}?geo_fields=confidence", SEARCH_MESSAGES_ROUTE)))
+        .body(request.to_string())
+        .send().await.unwrap();
+    let geo_tags = geo_tag_fields(&response.text().await.unwrap());
+
+    assert!(!geo_tags.is_empty());
+    assert!(geo_tags.iter().all(|fields| fields == &["confidence"]));
+}
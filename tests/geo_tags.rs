@@ -0,0 +1,123 @@
+mod common;
+
+// The message schema is built into the server binary, so the test compiles
+// its own copy to call ChatMessageSchema::to_geojson_feature.
+#[path = "../src/timestamp.rs"]
+mod timestamp;
+
+#[path = "../src/messages.rs"]
+mod messages;
+
+use common::*;
+use messages::ChatMessageSchema;
+
+/// This function fetches the test room's messages from a server started
+/// with the given geo-tag location type, returning each message's first
+/// geo-tag.
+async fn fetch_geo_tags(location_type: &str) -> Vec<serde_json::Value> {
+    let server = TestServer::start_with_args(&["--geo_location_type", location_type]);
+
+    let response = server.client().get(server.url(MESSAGES_ROUTE))
+        .send().await.unwrap();
+    let body: serde_json::Value = serde_json::from_str(&response.text().await.unwrap()).unwrap();
+
+    body["messages"].as_array().unwrap().iter()
+        .map(|message| message["geoTags"][0].clone())
+        .collect()
+}
+
+/// This function fetches the test room's messages from a server started
+/// with the given geo-tag location type.
+async fn fetch_messages(location_type: &str) -> Vec<ChatMessageSchema> {
+    let server = TestServer::start_with_args(&["--geo_location_type", location_type]);
+
+    let response = server.client().get(server.url(MESSAGES_ROUTE))
+        .send().await.unwrap();
+    let body: serde_json::Value = serde_json::from_str(&response.text().await.unwrap()).unwrap();
+
+    body["messages"].as_array().unwrap().iter()
+        .map(|message| ChatMessageSchema::try_from_json(message.to_string()).unwrap())
+        .collect()
+}
+
+/// This function parses the value as a GeoJSON geometry, failing the test
+/// if it isn't one.
+fn parse_geometry(value: &serde_json::Value) -> geojson::Value {
+    geojson::Geometry::from_json_value(value.clone())
+        .unwrap_or_else(|e| panic!("{} is not a GeoJSON geometry: {}", value, e))
+        .value
+}
+
+/// This function returns true if the position is a [longitude, latitude]
+/// pair in range.
+fn is_in_range(position: &geojson::Position) -> bool {
+    matches!(position.as_slice(), [longitude, latitude]
+        if (-180.0..=180.0).contains(longitude) && (-90.0..=90.0).contains(latitude))
+}
+
+/// This function returns true if the polygon's rings are closed, have four
+/// positions or more, and stay in range, which the GeoJSON library leaves
+/// to us to check.
+fn is_valid_polygon(rings: &geojson::PolygonType) -> bool {
+    !rings.is_empty() && rings.iter().all(|ring| ring.len() >= 4
+        && ring.iter().all(is_in_range)
+        && ring.first() == ring.last())
+}
+
+#[tokio::test]
+async fn point_geo_tags_have_point_coordinates() {
+    let geo_tags = fetch_geo_tags("point").await;
+    assert!(!geo_tags.is_empty());
+
+    for geo_tag in geo_tags {
+        assert_eq!(geo_tag["location"]["type"], "Point");
+
+        let geojson::Value::Point(point) = parse_geometry(&geo_tag["location"]["aoi"]) else {
+            panic!("{} is not a Point", geo_tag["location"]["aoi"]);
+        };
+        assert!(is_in_range(&point), "{:?}", point);
+
+        // The first region is the place the geo-tag points at, with its
+        // bounds given as [south, west, north, east].
+        let bounds = &geo_tag["regions"][0]["bounds"];
+        let (longitude, latitude) = (point[0], point[1]);
+        assert!(bounds[1].as_f64().unwrap() <= longitude && longitude <= bounds[3].as_f64().unwrap());
+        assert!(bounds[0].as_f64().unwrap() <= latitude && latitude <= bounds[2].as_f64().unwrap());
+    }
+}
+
+#[tokio::test]
+async fn polygon_geo_tags_have_polygon_rings() {
+    let geo_tags = fetch_geo_tags("polygon").await;
+    assert!(!geo_tags.is_empty());
+
+    for geo_tag in geo_tags {
+        assert_eq!(geo_tag["location"]["type"], "Polygon");
+
+        let geojson::Value::Polygon(rings) = parse_geometry(&geo_tag["location"]["aoi"]) else {
+            panic!("{} is not a Polygon", geo_tag["location"]["aoi"]);
+        };
+        assert!(is_valid_polygon(&rings), "{:?}", rings);
+    }
+}
+
+#[tokio::test]
+async fn messages_convert_to_geojson_features() {
+    let messages = fetch_messages("polygon").await;
+    assert!(!messages.is_empty());
+
+    for message in messages {
+        let feature = geojson::Feature::from_json_value(message.to_geojson_feature())
+            .expect("The message is not a GeoJSON Feature");
+
+        let Some(geojson::Value::Polygon(rings)) = feature.geometry.map(|geometry| geometry.value) else {
+            panic!("Feature {:?} has no Polygon geometry", feature.id);
+        };
+        assert!(is_valid_polygon(&rings), "{:?}", rings);
+
+        let properties = feature.properties.unwrap();
+        assert_eq!(feature.id, Some(geojson::feature::Id::String(message.id.clone())));
+        assert_eq!(properties["id"], message.id.as_str());
+        assert_eq!(properties["text"], message.text.as_str());
+    }
+}
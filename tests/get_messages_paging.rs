@@ -0,0 +1,43 @@
+mod common;
+
+use common::*;
+use reqwest::StatusCode;
+
+/// This function gets the messages with the given query string, returning
+/// their senders.
+async fn message_senders(server: &TestServer, query: &str) -> Vec<String> {
+    let response = server.client().get(server.url(&format!("{}{}", MESSAGES_ROUTE, query)))
+        .send().await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body: serde_json::Value = serde_json::from_str(&response.text().await.unwrap()).unwrap();
+    body["messages"].as_array().unwrap().iter()
+        .map(|message| String::from(message["sender"].as_str().unwrap()))
+        .collect()
+}
+
+#[tokio::test]
+async fn count_and_offset_select_a_page() {
+    let server = TestServer::start();
+
+    let all = message_senders(&server, "").await;
+    assert_eq!(all.len(), 10);
+
+    assert_eq!(message_senders(&server, "?count=3").await, all[..3]);
+    assert_eq!(message_senders(&server, "?count=3&offset=4").await, all[4..7]);
+    assert_eq!(message_senders(&server, "?count=5&offset=8").await, all[8..]);
+}
+
+#[tokio::test]
+async fn offsets_past_the_end_give_an_empty_page() {
+    let server = TestServer::start();
+
+    assert!(message_senders(&server, "?offset=20").await.is_empty());
+}
+
+#[tokio::test]
+async fn count_is_clamped_to_the_maximum() {
+    let server = TestServer::start_with_args(&["--max_get_messages", "4"]);
+
+    assert_eq!(message_senders(&server, "?count=8").await.len(), 4);
+}
@@ -0,0 +1,42 @@
+mod common;
+
+use common::*;
+use axum::body::Body;
+use hyper::{
+    Request,
+    StatusCode,
+};
+use hyper_util::rt::{
+    TokioExecutor,
+    TokioIo,
+};
+use tokio::net::TcpStream;
+
+/// This function requests the route over HTTP/2 with prior knowledge,
+/// returning the response's status, or None if the connection fails.
+async fn h2c_status(server: &TestServer, route: &str) -> Option<StatusCode> {
+    let stream = TcpStream::connect(server.address).await.unwrap();
+    let (mut sender, connection) = hyper::client::conn::http2::handshake(
+        TokioExecutor::new(), TokioIo::new(stream)).await.ok()?;
+    tokio::spawn(connection);
+
+    let request = Request::get(server.url(route))
+        .body(Body::empty())
+        .unwrap();
+
+    sender.send_request(request).await.ok().map(|response| response.status())
+}
+
+#[tokio::test]
+async fn messages_are_served_over_h2c() {
+    let server = TestServer::start_with_args(&["--http2"]);
+
+    assert_eq!(h2c_status(&server, MESSAGES_ROUTE).await, Some(StatusCode::OK));
+}
+
+#[tokio::test]
+async fn h2c_is_refused_by_default() {
+    let server = TestServer::start();
+
+    assert_eq!(h2c_status(&server, MESSAGES_ROUTE).await, None);
+}
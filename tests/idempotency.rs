@@ -0,0 +1,89 @@
+mod common;
+
+use common::*;
+use futures_util::StreamExt;
+use reqwest::StatusCode;
+use std::time::Duration;
+use tokio::net::TcpStream;
+use tokio_tungstenite::{
+    tungstenite::Message,
+    MaybeTlsStream,
+    WebSocketStream,
+};
+
+const SEND_REQUEST: &str = r#"{
+    "classification": "UNCLASSIFIED",
+    "domainId": "chatsurferxmppunclass",
+    "message": "Posted exactly once",
+    "nickname": "Tester",
+    "roomName": "edge-view-test-room"
+}"#;
+
+/// How long to collect frames after the posts.
+const COLLECT_PERIOD: Duration = Duration::from_secs(1);
+
+/// This function posts the message with the given idempotency key.
+async fn post_with_key(server: &TestServer, key: &str) -> reqwest::Response {
+    server.client().post(server.url(NEW_MESSAGE_ROUTE))
+        .header("Idempotency-Key", key)
+        .body(SEND_REQUEST)
+        .send().await.unwrap()
+}
+
+/// This function counts the copies of the posted message that arrive on
+/// the stream within the collection period.
+async fn count_posted(stream: &mut WebSocketStream<MaybeTlsStream<TcpStream>>) -> usize {
+    let mut copies = 0;
+    let deadline = tokio::time::Instant::now() + COLLECT_PERIOD;
+
+    while let Ok(Some(message)) = tokio::time::timeout_at(deadline, stream.next()).await {
+        if let Message::Text(text) = message.unwrap() {
+            let body: serde_json::Value = serde_json::from_str(&text).unwrap();
+
+            if body["text"] == "Posted exactly once" {
+                copies += 1;
+            }
+        }
+    }
+
+    copies
+}
+
+#[tokio::test]
+async fn retried_posts_reach_subscribers_once() {
+    let server = TestServer::start();
+    let mut stream = server.connect_ws(WS_SINGLE_ROOM_ROUTE).await;
+
+    let first = post_with_key(&server, "retry-me").await;
+    let second = post_with_key(&server, "retry-me").await;
+    assert_eq!(first.status(), StatusCode::NO_CONTENT);
+    assert_eq!(second.status(), first.status());
+
+    assert_eq!(count_posted(&mut stream).await, 1);
+}
+
+#[tokio::test]
+async fn posts_with_different_keys_are_both_delivered() {
+    let server = TestServer::start();
+    let mut stream = server.connect_ws(WS_SINGLE_ROOM_ROUTE).await;
+
+    post_with_key(&server, "first").await;
+    post_with_key(&server, "second").await;
+
+    assert_eq!(count_posted(&mut stream).await, 2);
+}
+
+#[tokio::test]
+async fn concurrent_retries_reach_subscribers_once() {
+    let server = TestServer::start();
+    let mut stream = server.connect_ws(WS_SINGLE_ROOM_ROUTE).await;
+
+    let responses = futures_util::future::join_all(
+        (0..8).map(|_| post_with_key(&server, "race-me"))).await;
+
+    for response in responses {
+        assert_eq!(response.status(), StatusCode::NO_CONTENT);
+    }
+
+    assert_eq!(count_posted(&mut stream).await, 1);
+}
@@ -0,0 +1,45 @@
+mod common;
+
+use common::*;
+use reqwest::StatusCode;
+
+const INJECT_ROUTE: &str = "/api/test/inject";
+
+/// This function fetches a generated message to use as the basis of an
+/// injected one, with its first geo-tag given the number of regions.
+async fn message_with_regions(server: &TestServer, count: usize) -> serde_json::Value {
+    let response = server.client().get(server.url(MESSAGES_ROUTE))
+        .send().await.unwrap();
+    let body: serde_json::Value = serde_json::from_str(&response.text().await.unwrap()).unwrap();
+
+    let mut message = body["messages"][0].clone();
+    let region = message["geoTags"][0]["regions"][0].clone();
+    message["geoTags"][0]["regions"] = serde_json::Value::Array(vec![region; count]);
+
+    message
+}
+
+#[tokio::test]
+async fn messages_with_too_many_regions_are_rejected() {
+    let server = TestServer::start();
+    let message = message_with_regions(&server, 6).await;
+
+    let response = server.client().post(server.url(INJECT_ROUTE))
+        .body(message.to_string())
+        .send().await.unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+    let body: serde_json::Value = serde_json::from_str(&response.text().await.unwrap()).unwrap();
+    assert_eq!(body["fieldErrors"][0]["fieldName"], "regions");
+}
+
+#[tokio::test]
+async fn messages_within_the_limit_are_stored() {
+    let server = TestServer::start();
+    let message = message_with_regions(&server, 5).await;
+
+    let response = server.client().post(server.url(INJECT_ROUTE))
+        .body(message.to_string())
+        .send().await.unwrap();
+    assert_eq!(response.status(), StatusCode::CREATED);
+}
@@ -0,0 +1,33 @@
+mod common;
+
+use common::*;
+use reqwest::StatusCode;
+
+#[tokio::test]
+async fn responses_carry_the_configured_instance_name() {
+    let server = TestServer::start_with_args(&["--instance_name", "mock-a"]);
+
+    let response = server.client().get(server.url(MESSAGES_ROUTE)).send().await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()["x-mock-instance"], "mock-a");
+
+    let response = server.client().get(server.url("/no/such/route")).send().await.unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    assert_eq!(response.headers()["x-mock-instance"], "mock-a");
+
+    // The WebSocket upgrade is stamped too.
+    let (_, response) = tokio_tungstenite::connect_async(
+        format!("ws://{}{}", server.address, WS_SINGLE_ROOM_ROUTE))
+        .await
+        .expect("Unable to open the WebSocket connection");
+    assert_eq!(response.headers()["x-mock-instance"], "mock-a");
+}
+
+#[tokio::test]
+async fn the_instance_name_defaults_to_the_host_name() {
+    let server = TestServer::start();
+
+    let response = server.client().get(server.url(MESSAGES_ROUTE)).send().await.unwrap();
+    let instance = response.headers()["x-mock-instance"].to_str().unwrap();
+    assert!(!instance.is_empty());
+}
@@ -0,0 +1,22 @@
+mod common;
+
+use common::*;
+
+const VALID_CLASSIFICATIONS: [&str; 4] = ["UNCLASSIFIED", "CONFIDENTIAL", "SECRET", "TOP SECRET"];
+
+#[tokio::test]
+async fn every_message_can_be_given_an_invalid_classification() {
+    let server = TestServer::start_with_args(&["--invalid_classification_rate", "1.0"]);
+
+    let response = server.client().get(server.url(MESSAGES_ROUTE))
+        .send().await.unwrap();
+    let body: serde_json::Value = serde_json::from_str(&response.text().await.unwrap()).unwrap();
+    let messages = body["messages"].as_array().unwrap();
+
+    assert!(!messages.is_empty());
+
+    for message in messages {
+        let classification = message["classification"].as_str().unwrap();
+        assert!(!VALID_CLASSIFICATIONS.contains(&classification), "{:?}", classification);
+    }
+}
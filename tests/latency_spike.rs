@@ -0,0 +1,30 @@
+mod common;
+
+use common::*;
+use std::time::{
+    Duration,
+    Instant,
+};
+
+#[tokio::test]
+async fn responses_slow_down_during_spikes() {
+    // Every second, responses are delayed by 300ms for half a second.
+    let server = TestServer::start_with_args(&["--latency_spike", "1s/300ms/500ms"]);
+    let mut durations = Vec::new();
+
+    // Sample across a couple of periods, so that some requests land in a
+    // spike and some don't.
+    let sampling = Instant::now();
+
+    while sampling.elapsed() < Duration::from_secs(2) {
+        let started = Instant::now();
+        server.client().get(server.url(GET_API_KEY_ROUTE))
+            .send().await.unwrap();
+        durations.push(started.elapsed());
+
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+
+    assert!(durations.iter().any(|duration| *duration >= Duration::from_millis(300)));
+    assert!(durations.iter().any(|duration| *duration < Duration::from_millis(100)));
+}
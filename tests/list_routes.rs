@@ -0,0 +1,65 @@
+mod common;
+
+use common::*;
+use reqwest::{
+    Method,
+    StatusCode,
+};
+
+const LIST_ROUTES_ROUTE: &str = "/api/test/routes";
+
+/// This function returns the method and path of each route the server
+/// lists.
+async fn listed_routes(server: &TestServer) -> Vec<(Method, String)> {
+    let response = server.client().get(server.url(LIST_ROUTES_ROUTE)).send().await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body: serde_json::Value = serde_json::from_str(&response.text().await.unwrap()).unwrap();
+    body.as_array().unwrap().iter()
+        .map(|route| (
+            route["method"].as_str().unwrap().parse().unwrap(),
+            String::from(route["path"].as_str().unwrap()),
+        ))
+        .collect()
+}
+
+#[tokio::test]
+async fn the_listed_routes_include_the_chatsurfer_api() {
+    let server = TestServer::start();
+    let routes = listed_routes(&server).await;
+
+    for (method, path) in [
+        (Method::GET, GET_API_KEY_ROUTE),
+        (Method::GET, MESSAGES_ROUTE),
+        (Method::POST, NEW_MESSAGE_ROUTE),
+        (Method::POST, SEARCH_MESSAGES_ROUTE),
+        (Method::GET, WS_SINGLE_ROOM_ROUTE),
+        (Method::GET, LIST_ROUTES_ROUTE),
+    ] {
+        assert!(routes.contains(&(method.clone(), String::from(path))), "{} {} isn't listed", method, path);
+    }
+}
+
+#[tokio::test]
+async fn every_listed_route_is_served_with_its_method() {
+    let server = TestServer::start();
+    let client = server.client();
+
+    for (method, path) in listed_routes(&server).await {
+        // Fill in any path parameters.
+        let path = path.split('/')
+            .map(|segment| if segment.starts_with(':') { "test" } else { segment })
+            .collect::<Vec<&str>>()
+            .join("/");
+
+        // The requests carry no body or parameters, so they may well be
+        // refused, but not because the route or method is unknown.
+        let response = client.request(method.clone(), server.url(&path)).send().await.unwrap();
+        assert_ne!(response.status(), StatusCode::NOT_FOUND, "{} {}", method, path);
+        assert_ne!(response.status(), StatusCode::METHOD_NOT_ALLOWED, "{} {}", method, path);
+
+        let other_method = if method == Method::GET { Method::POST } else { Method::GET };
+        let response = client.request(other_method.clone(), server.url(&path)).send().await.unwrap();
+        assert_eq!(response.status(), StatusCode::METHOD_NOT_ALLOWED, "{} {}", other_method, path);
+    }
+}
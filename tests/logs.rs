@@ -0,0 +1,55 @@
+mod common;
+
+use common::*;
+use reqwest::StatusCode;
+
+const LOGS_ROUTE: &str = "/api/test/logs";
+
+#[tokio::test]
+async fn recent_errors_can_be_found_in_the_logs() {
+    let server = TestServer::start_with_args(&["--enable_admin"]);
+
+    let response = server.client().post(server.url(NEW_MESSAGE_ROUTE))
+        .send().await.unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+    let response = server.client().get(server.url(&format!("{}?n=20", LOGS_ROUTE)))
+        .send().await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let records: Vec<serde_json::Value> = serde_json::from_str(&response.text().await.unwrap()).unwrap();
+    assert!(records.len() <= 20);
+
+    let record = records.iter()
+        .find(|record| record["message"].as_str().unwrap().contains("Request body is missing"))
+        .expect("The error wasn't logged");
+    assert_eq!(record["level"], "DEBUG");
+    assert!(record["target"].is_string());
+    assert!(record["timestamp"].is_string());
+}
+
+#[tokio::test]
+async fn the_logs_need_admin() {
+    let server = TestServer::start();
+
+    let response = server.client().get(server.url(LOGS_ROUTE))
+        .send().await.unwrap();
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+}
+
+#[tokio::test]
+async fn the_startup_banner_summarizes_the_launch() {
+    let server = TestServer::start_with_args(&["--enable_admin"]);
+
+    let response = server.client().get(server.url(&format!("{}?n=1000", LOGS_ROUTE)))
+        .send().await.unwrap();
+    let records: Vec<serde_json::Value> = serde_json::from_str(&response.text().await.unwrap()).unwrap();
+
+    let banner = records.iter()
+        .filter_map(|record| record["message"].as_str())
+        .find(|message| message.starts_with("WebSocket-EchoServer starting"))
+        .expect("The banner wasn't logged");
+    assert!(banner.contains(&format!("listen=\"http://{}\"", server.address)), "{}", banner);
+    assert!(banner.contains("tls=\"off\""), "{}", banner);
+    assert!(banner.contains("route_count="), "{}", banner);
+}
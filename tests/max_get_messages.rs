@@ -0,0 +1,30 @@
+mod common;
+
+use common::*;
+use reqwest::StatusCode;
+
+#[tokio::test]
+async fn long_responses_are_truncated() {
+    let server = TestServer::start_with_args(&["--max_get_messages", "4"]);
+
+    let response = server.client().get(server.url(MESSAGES_ROUTE)).send().await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()["x-truncated"], "true");
+
+    let body: serde_json::Value = serde_json::from_str(&response.text().await.unwrap()).unwrap();
+    assert_eq!(body["messages"].as_array().unwrap().len(), 4);
+    assert_eq!(body["total"], 10);
+}
+
+#[tokio::test]
+async fn short_responses_are_left_alone() {
+    let server = TestServer::start_with_args(&["--max_get_messages", "10"]);
+
+    let response = server.client().get(server.url(MESSAGES_ROUTE)).send().await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert!(response.headers().get("x-truncated").is_none());
+
+    let body: serde_json::Value = serde_json::from_str(&response.text().await.unwrap()).unwrap();
+    assert_eq!(body["messages"].as_array().unwrap().len(), 10);
+    assert!(body.get("total").is_none());
+}
@@ -0,0 +1,40 @@
+mod common;
+
+use common::*;
+use reqwest::StatusCode;
+
+const BUSY_ROUTE: &str = "/api/test/busy";
+
+#[tokio::test]
+async fn requests_over_the_limit_are_refused() {
+    let server = TestServer::start_with_args(&["--max_inflight", "2"]);
+
+    // Hold each request open long enough for them all to overlap.
+    let requests = (0..6).map(|_| {
+        let url = server.url(&format!("{}?ms=500&offload=true", BUSY_ROUTE));
+        async move { reqwest::Client::new().get(url).send().await.unwrap().status() }
+    });
+    let statuses = futures_util::future::join_all(requests).await;
+
+    let refused = statuses.iter().filter(|&&status| status == StatusCode::SERVICE_UNAVAILABLE).count();
+    let served = statuses.iter().filter(|&&status| status == StatusCode::OK).count();
+
+    assert!(refused > 0, "{:?}", statuses);
+    assert!(served > 0 && served <= 2, "{:?}", statuses);
+    assert_eq!(refused + served, statuses.len());
+}
+
+#[tokio::test]
+async fn the_limit_is_shared_between_routes() {
+    let server = TestServer::start_with_args(&["--max_inflight", "1"]);
+
+    // Hold the only permit with a slow request to one route.
+    let url = server.url(&format!("{}?ms=1000&offload=true", BUSY_ROUTE));
+    let busy = tokio::spawn(async move { reqwest::Client::new().get(url).send().await.unwrap().status() });
+    tokio::time::sleep(std::time::Duration::from_millis(300)).await;
+
+    let response = server.client().get(server.url(MESSAGES_ROUTE)).send().await.unwrap();
+    assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+
+    assert_eq!(busy.await.unwrap(), StatusCode::OK);
+}
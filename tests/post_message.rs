@@ -0,0 +1,123 @@
+mod common;
+
+use common::*;
+use reqwest::StatusCode;
+
+/// This function builds a send request for the test room with the given
+/// message text.
+fn send_request(message: &str) -> String {
+    serde_json::json!({
+        "classification":   "UNCLASSIFIED",
+        "domainId":         "chatsurferxmppunclass",
+        "message":          message,
+        "nickname":         "Tester",
+        "roomName":         "edge-view-test-room",
+    }).to_string()
+}
+
+#[tokio::test]
+async fn reserved_nicknames_are_rejected() {
+    let server = TestServer::start_with_args(&["--reserved_nicknames", "system,TESTER"]);
+
+    let response = server.client().post(server.url(NEW_MESSAGE_ROUTE))
+        .body(send_request("Hello"))
+        .send().await.unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+    // The nicknames are compared without regard to case.
+    let body: serde_json::Value = serde_json::from_str(&response.text().await.unwrap()).unwrap();
+    assert_eq!(body["fieldErrors"][0]["fieldName"], "nickname");
+    assert_eq!(body["fieldErrors"][0]["messageCode"], "NicknameIsReserved");
+    assert_eq!(body["fieldErrors"][0]["rejectedValue"], "Tester");
+}
+
+#[tokio::test]
+async fn other_nicknames_are_accepted() {
+    let server = TestServer::start_with_args(&["--reserved_nicknames", "system"]);
+
+    let response = server.client().post(server.url(NEW_MESSAGE_ROUTE))
+        .body(send_request("Hello"))
+        .send().await.unwrap();
+    assert_eq!(response.status(), StatusCode::NO_CONTENT);
+}
+
+#[tokio::test]
+async fn posts_can_return_the_created_message() {
+    let server = TestServer::start_with_args(&["--post_success_status", "201"]);
+
+    let response = server.client().post(server.url(NEW_MESSAGE_ROUTE))
+        .body(send_request("Created for the client"))
+        .send().await.unwrap();
+    assert_eq!(response.status(), StatusCode::CREATED);
+
+    let body: serde_json::Value = serde_json::from_str(&response.text().await.unwrap()).unwrap();
+    assert_eq!(body["text"], "Created for the client");
+    assert_eq!(body["roomName"], "edge-view-test-room");
+    assert!(!body["id"].as_str().unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn posts_return_no_body_by_default() {
+    let server = TestServer::start();
+
+    let response = server.client().post(server.url(NEW_MESSAGE_ROUTE))
+        .body(send_request("Nothing to say back"))
+        .send().await.unwrap();
+    assert_eq!(response.status(), StatusCode::NO_CONTENT);
+    assert!(response.text().await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn over_length_messages_are_rejected() {
+    let server = TestServer::start_with_args(&["--max_message_len", "16"]);
+
+    let response = server.client().post(server.url(NEW_MESSAGE_ROUTE))
+        .body(send_request("This message is longer than sixteen bytes"))
+        .send().await.unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+    let body: serde_json::Value = serde_json::from_str(&response.text().await.unwrap()).unwrap();
+    assert_eq!(body["fieldErrors"][0]["fieldName"], "message");
+    assert_eq!(body["fieldErrors"][0]["messageCode"], "MessageTooLong");
+
+    let response = server.client().post(server.url(NEW_MESSAGE_ROUTE))
+        .body(send_request("Short enough"))
+        .send().await.unwrap();
+    assert_eq!(response.status(), StatusCode::NO_CONTENT);
+}
+
+#[tokio::test]
+async fn empty_bodies_get_the_body_missing_error() {
+    let server = TestServer::start();
+
+    let response = server.client().post(server.url(NEW_MESSAGE_ROUTE))
+        .send().await.unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+    let body: serde_json::Value = serde_json::from_str(&response.text().await.unwrap()).unwrap();
+    assert_eq!(body, serde_json::json!({
+        "classification":   "UNCLASSIFIED",
+        "code":             400,
+        "fieldErrors":      [],
+        "message":          "Request body is missing or not readable.",
+    }));
+}
+
+#[tokio::test]
+async fn posted_messages_become_visible_after_the_delay() {
+    let server = TestServer::start_with_args(&["--write_visibility_delay_ms", "500"]);
+
+    let is_visible = || async {
+        let response = server.client().get(server.url(MESSAGES_ROUTE)).send().await.unwrap();
+        response.text().await.unwrap().contains("Eventually visible")
+    };
+
+    let response = server.client().post(server.url(NEW_MESSAGE_ROUTE))
+        .body(send_request("Eventually visible"))
+        .send().await.unwrap();
+    assert_eq!(response.status(), StatusCode::NO_CONTENT);
+    assert!(!is_visible().await);
+
+    tokio::time::sleep(std::time::Duration::from_millis(600)).await;
+    assert!(is_visible().await);
+}
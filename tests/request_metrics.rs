@@ -0,0 +1,55 @@
+mod common;
+
+use common::*;
+use reqwest::StatusCode;
+
+const LOGS_ROUTE: &str = "/api/test/logs";
+const BUSY_ROUTE: &str = "/api/test/busy";
+const ROOM_CLASSIFICATION_ROUTE: &str = "/api/chat/classification/:domain_id/:room_name";
+
+/// This function returns the fields of the request metrics events logged
+/// so far, one map of names to values per request.
+async fn request_metrics(server: &TestServer) -> Vec<serde_json::Map<String, serde_json::Value>> {
+    let response = server.client().get(server.url(&format!("{}?n=1000", LOGS_ROUTE)))
+        .send().await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let records: Vec<serde_json::Value> = serde_json::from_str(&response.text().await.unwrap()).unwrap();
+
+    // The fields follow the message as name=value, with strings quoted.
+    records.iter()
+        .filter_map(|record| record["message"].as_str()?.strip_prefix("Request complete "))
+        .map(|fields| fields.split(' ')
+            .map(|field| {
+                let (name, value) = field.split_once('=').unwrap();
+                (String::from(name), serde_json::from_str(value).unwrap())
+            })
+            .collect())
+        .collect()
+}
+
+#[tokio::test]
+async fn each_request_is_recorded_under_its_route() {
+    let server = TestServer::start_with_args(&["--enable_admin"]);
+    let client = server.client();
+
+    client.get(server.url(MESSAGES_ROUTE)).send().await.unwrap();
+    client.get(server.url("/api/chat/classification/chatsurferxmppunclass/edge-view-test-room"))
+        .send().await.unwrap();
+    client.get(server.url(&format!("{}?ms=100", BUSY_ROUTE))).send().await.unwrap();
+
+    let metrics = request_metrics(&server).await;
+    let find = |route: &str| metrics.iter()
+        .find(|fields| fields["route"] == route)
+        .unwrap_or_else(|| panic!("No metrics were recorded for {}", route));
+
+    let messages = find(MESSAGES_ROUTE);
+    assert_eq!(messages["status"], 200);
+    assert!(messages["response_bytes"].as_u64().unwrap() > 0);
+
+    // Routes with parameters are recorded under their template.
+    assert_eq!(find(ROOM_CLASSIFICATION_ROUTE)["status"], 200);
+
+    // The latency covers the time spent in the handler.
+    assert!(find(BUSY_ROUTE)["duration_ms"].as_f64().unwrap() >= 100.0);
+}
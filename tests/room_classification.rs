@@ -0,0 +1,46 @@
+mod common;
+
+use common::*;
+
+const INJECT_ROUTE: &str = "/api/test/inject";
+const TEST_ROOM_CLASSIFICATION_ROUTE: &str =
+    "/api/chat/classification/chatsurferxmppunclass/edge-view-test-room";
+
+/// This function returns the classification the server reports for the
+/// room at the given route.
+async fn room_classification(server: &TestServer, route: &str) -> serde_json::Value {
+    let response = server.client().get(server.url(route))
+        .send().await.unwrap();
+    let body: serde_json::Value = serde_json::from_str(&response.text().await.unwrap()).unwrap();
+
+    body["classification"].clone()
+}
+
+#[tokio::test]
+async fn the_highest_classification_is_reported() {
+    let server = TestServer::start();
+    assert_eq!(room_classification(&server, TEST_ROOM_CLASSIFICATION_ROUTE).await, "UNCLASSIFIED");
+
+    // Store a SECRET copy of one of the room's messages.
+    let response = server.client().get(server.url(MESSAGES_ROUTE))
+        .send().await.unwrap();
+    let body: serde_json::Value = serde_json::from_str(&response.text().await.unwrap()).unwrap();
+
+    let mut message = body["messages"][0].clone();
+    message["classification"] = serde_json::json!("SECRET");
+
+    server.client().post(server.url(INJECT_ROUTE))
+        .body(message.to_string())
+        .send().await.unwrap();
+
+    assert_eq!(room_classification(&server, TEST_ROOM_CLASSIFICATION_ROUTE).await, "SECRET");
+}
+
+#[tokio::test]
+async fn empty_rooms_report_the_default() {
+    let server = TestServer::start();
+
+    let classification =
+        room_classification(&server, "/api/chat/classification/chatsurferxmppunclass/empty-room").await;
+    assert_eq!(classification, "UNCLASSIFIED");
+}
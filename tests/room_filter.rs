@@ -0,0 +1,87 @@
+mod common;
+
+use common::*;
+use futures_util::StreamExt;
+use reqwest::StatusCode;
+use std::time::Duration;
+use tokio_tungstenite::tungstenite::Message;
+
+const OTHER_ROOM_NAME: &str = "edge-view-other-room";
+
+/// This function posts a message with the given text to the given room.
+async fn post_to_room(server: &TestServer, room_name: &str, text: &str) {
+    let request = serde_json::json!({
+        "classification":   "UNCLASSIFIED",
+        "domainId":         "chatsurferxmppunclass",
+        "message":          text,
+        "nickname":         "Tester",
+        "roomName":         room_name,
+    });
+
+    let response = server.client().post(server.url(NEW_MESSAGE_ROUTE))
+        .body(request.to_string())
+        .send().await.unwrap();
+    assert!(response.status().is_success());
+}
+
+/// This function searches for Zeppelin with the given room filter,
+/// returning the rooms of the matching messages.
+async fn search_rooms(server: &TestServer, room_filter: serde_json::Value) -> Vec<String> {
+    let request = serde_json::json!({
+        "keywordFilter":            { "query": "Zeppelin" },
+        "roomFilter":               room_filter,
+        "UserHighClassification":   "UNCLASSIFIED",
+    });
+
+    let response = server.client().post(server.url(SEARCH_MESSAGES_ROUTE))
+        .body(request.to_string())
+        .send().await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body: serde_json::Value = serde_json::from_str(&response.text().await.unwrap()).unwrap();
+    body["messages"].as_array().unwrap().iter()
+        .map(|message| String::from(message["roomName"].as_str().unwrap()))
+        .collect()
+}
+
+#[tokio::test]
+async fn searches_only_cover_the_filtered_rooms() {
+    let server = TestServer::start();
+
+    post_to_room(&server, "edge-view-test-room", "Zeppelin in the test room").await;
+    post_to_room(&server, OTHER_ROOM_NAME, "Zeppelin in the other room").await;
+
+    let mut unfiltered = search_rooms(&server, serde_json::Value::Null).await;
+    unfiltered.sort();
+    assert_eq!(unfiltered, [OTHER_ROOM_NAME, "edge-view-test-room"]);
+
+    let filtered = search_rooms(&server, serde_json::json!({
+        "domains": { "chatsurferxmppunclass": { "properties": [OTHER_ROOM_NAME] } },
+    })).await;
+    assert_eq!(filtered, [OTHER_ROOM_NAME]);
+}
+
+#[tokio::test]
+async fn subscribers_see_nothing_from_other_rooms() {
+    let server = TestServer::start();
+    let mut stream = server.connect_ws(WS_SINGLE_ROOM_ROUTE).await;
+
+    post_to_room(&server, OTHER_ROOM_NAME, "Zeppelin in the other room").await;
+    post_to_room(&server, "edge-view-test-room", "Zeppelin in the test room").await;
+
+    let mut rooms = Vec::new();
+    let deadline = tokio::time::Instant::now() + Duration::from_secs(1);
+
+    while let Ok(Some(message)) = tokio::time::timeout_at(deadline, stream.next()).await {
+        if let Message::Text(text) = message.unwrap() {
+            let body: serde_json::Value = serde_json::from_str(&text).unwrap();
+
+            if let Some(room_name) = body["roomName"].as_str() {
+                rooms.push(String::from(room_name));
+            }
+        }
+    }
+
+    assert!(rooms.contains(&String::from("edge-view-test-room")));
+    assert!(!rooms.contains(&String::from(OTHER_ROOM_NAME)), "{:?}", rooms);
+}
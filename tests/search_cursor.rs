@@ -0,0 +1,52 @@
+mod common;
+
+use common::*;
+use reqwest::StatusCode;
+
+/// This function searches for the test keyword a page at a time, from the
+/// given cursor.
+async fn search_page(server: &TestServer, cursor: Option<&str>) -> (StatusCode, serde_json::Value) {
+    let request = serde_json::json!({
+        "cursor":                   cursor,
+        "keywordFilter":            { "query": "Antediluvian" },
+        "limit":                    1,
+        "UserHighClassification":   "UNCLASSIFIED",
+    });
+
+    let response = server.client().post(server.url(SEARCH_MESSAGES_ROUTE))
+        .body(request.to_string())
+        .send().await.unwrap();
+    let status = response.status();
+
+    (status, serde_json::from_str(&response.text().await.unwrap()).unwrap())
+}
+
+#[tokio::test]
+async fn cursors_round_trip() {
+    let server = TestServer::start();
+
+    let (status, first_page) = search_page(&server, None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(first_page["messages"].as_array().unwrap().len(), 1);
+
+    let cursor = first_page["nextCursorMark"].as_str().unwrap();
+    let (status, second_page) = search_page(&server, Some(cursor)).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(second_page["messages"][0]["sender"], "Joe");
+}
+
+#[tokio::test]
+async fn tampered_cursors_are_rejected() {
+    let server = TestServer::start();
+
+    let (_, first_page) = search_page(&server, None).await;
+    let cursor = first_page["nextCursorMark"].as_str().unwrap();
+
+    // Point the cursor at a different offset, keeping the signature.
+    let mut tampered = String::from(cursor);
+    tampered.replace_range(15..16, if &cursor[15..16] == "2" { "3" } else { "2" });
+
+    let (status, body) = search_page(&server, Some(&tampered)).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["fieldErrors"][0]["messageCode"], "CursorIsInvalid");
+}
@@ -0,0 +1,57 @@
+mod common;
+
+use common::*;
+use reqwest::StatusCode;
+
+/// This function posts a message with the given text to the test room.
+async fn post_message(server: &TestServer, text: &str) {
+    let request = serde_json::json!({
+        "classification":   "UNCLASSIFIED",
+        "domainId":         "chatsurferxmppunclass",
+        "message":          text,
+        "nickname":         "Tester",
+        "roomName":         "edge-view-test-room",
+    });
+
+    let response = server.client().post(server.url(NEW_MESSAGE_ROUTE))
+        .body(request.to_string())
+        .send().await.unwrap();
+    assert!(response.status().is_success());
+}
+
+/// This function searches for Zeppelin with highlighting, returning the
+/// text of the first result.
+async fn highlighted_text(server: &TestServer) -> String {
+    let request = serde_json::json!({
+        "keywordFilter":            { "query": "Zeppelin" },
+        "highlightResults":         true,
+        "UserHighClassification":   "UNCLASSIFIED",
+    });
+
+    let response = server.client().post(server.url(SEARCH_MESSAGES_ROUTE))
+        .body(request.to_string())
+        .send().await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body: serde_json::Value = serde_json::from_str(&response.text().await.unwrap()).unwrap();
+    String::from(body["messages"][0]["text"].as_str().unwrap())
+}
+
+#[tokio::test]
+async fn matches_are_highlighted_with_em_by_default() {
+    let server = TestServer::start();
+    post_message(&server, "A Zeppelin overhead").await;
+
+    assert_eq!(highlighted_text(&server).await, "A <em>Zeppelin</em> overhead");
+}
+
+#[tokio::test]
+async fn matches_are_highlighted_with_the_configured_markers() {
+    let server = TestServer::start_with_args(&[
+        "--highlight_prefix", "[[",
+        "--highlight_suffix", "]]",
+    ]);
+    post_message(&server, "A Zeppelin overhead").await;
+
+    assert_eq!(highlighted_text(&server).await, "A [[Zeppelin]] overhead");
+}
@@ -0,0 +1,168 @@
+mod common;
+
+use common::*;
+use reqwest::StatusCode;
+
+/// This function posts a message with the given text to the test room.
+async fn post_message(server: &TestServer, text: &str) {
+    let request = serde_json::json!({
+        "classification":   "UNCLASSIFIED",
+        "domainId":         "chatsurferxmppunclass",
+        "message":          text,
+        "nickname":         "Tester",
+        "roomName":         "edge-view-test-room",
+    });
+
+    let response = server.client().post(server.url(NEW_MESSAGE_ROUTE))
+        .body(request.to_string())
+        .send().await.unwrap();
+    assert!(response.status().is_success());
+}
+
+#[tokio::test]
+async fn repeated_keywords_rank_higher() {
+    let server = TestServer::start();
+
+    // Post the weaker match first, so that insertion order would put it
+    // ahead.
+    post_message(&server, "Zeppelin spotted").await;
+    post_message(&server, "Zeppelin after Zeppelin").await;
+
+    let request = serde_json::json!({
+        "keywordFilter":            { "query": "Zeppelin" },
+        "sort":                     { "orders": [["DESC", "RELEVANCE"]] },
+        "UserHighClassification":   "UNCLASSIFIED",
+    });
+
+    let response = server.client().post(server.url(&format!("{}?scores=true", SEARCH_MESSAGES_ROUTE)))
+        .body(request.to_string())
+        .send().await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body: serde_json::Value = serde_json::from_str(&response.text().await.unwrap()).unwrap();
+    let messages = body["messages"].as_array().unwrap();
+    assert_eq!(messages.len(), 2);
+    assert_eq!(messages[0]["text"], "Zeppelin after Zeppelin");
+    assert!(messages[0]["score"].as_u64().unwrap() > messages[1]["score"].as_u64().unwrap());
+}
+
+/// This function searches for the given query, asking for scores and the
+/// keyword breakdown.
+async fn search_with_breakdown(server: &TestServer, query: &str) -> serde_json::Value {
+    let request = serde_json::json!({
+        "keywordFilter":            { "query": query },
+        "UserHighClassification":   "UNCLASSIFIED",
+    });
+
+    let response = server.client()
+        .post(server.url(&format!("{}?scores=true&breakdown=true", SEARCH_MESSAGES_ROUTE)))
+        .body(request.to_string())
+        .send().await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    serde_json::from_str(&response.text().await.unwrap()).unwrap()
+}
+
+#[tokio::test]
+async fn the_breakdown_counts_messages_per_keyword() {
+    let server = TestServer::start();
+
+    post_message(&server, "Zeppelin over Quagga").await;
+    post_message(&server, "Zeppelin spotted").await;
+
+    let body = search_with_breakdown(&server, "Zeppelin Quagga Wombat").await;
+    assert_eq!(body["keywordCounts"], serde_json::json!({
+        "Zeppelin": 2,
+        "Quagga":   1,
+        "Wombat":   0,
+    }));
+}
+
+#[tokio::test]
+async fn repeated_query_keywords_count_once() {
+    let server = TestServer::start();
+
+    post_message(&server, "Zeppelin spotted").await;
+
+    let once = search_with_breakdown(&server, "Zeppelin").await;
+    let twice = search_with_breakdown(&server, "Zeppelin  Zeppelin").await;
+
+    assert_eq!(twice["keywordCounts"], serde_json::json!({ "Zeppelin": 1 }));
+    assert_eq!(twice["messages"][0]["score"], once["messages"][0]["score"]);
+}
+
+#[tokio::test]
+async fn search_without_breakdown_omits_the_counts() {
+    let server = TestServer::start();
+
+    let request = serde_json::json!({
+        "keywordFilter":            { "query": "test" },
+        "UserHighClassification":   "UNCLASSIFIED",
+    });
+
+    let response = server.client().post(server.url(SEARCH_MESSAGES_ROUTE))
+        .body(request.to_string())
+        .send().await.unwrap();
+    let body: serde_json::Value = serde_json::from_str(&response.text().await.unwrap()).unwrap();
+    assert!(body.get("keywordCounts").is_none());
+}
+
+#[tokio::test]
+async fn the_breakdown_only_counts_messages_within_the_time_filter() {
+    let server = TestServer::start();
+
+    post_message(&server, "Zeppelin spotted").await;
+
+    // Every message so far is older than the start of the filter.
+    let request = serde_json::json!({
+        "keywordFilter":            { "query": "Zeppelin" },
+        "timeFilter":               { "startDateTime": "2999-01-01T00:00:00Z" },
+        "UserHighClassification":   "UNCLASSIFIED",
+    });
+
+    let response = server.client()
+        .post(server.url(&format!("{}?breakdown=true", SEARCH_MESSAGES_ROUTE)))
+        .body(request.to_string())
+        .send().await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body: serde_json::Value = serde_json::from_str(&response.text().await.unwrap()).unwrap();
+    assert_eq!(body["total"], 0);
+    assert_eq!(body["keywordCounts"], serde_json::json!({ "Zeppelin": 0 }));
+}
+
+/// This function searches for Zeppelin sorted by relevance in the given
+/// direction, returning the texts of the results in order.
+async fn texts_by_relevance(server: &TestServer, direction: &str) -> Vec<String> {
+    let request = serde_json::json!({
+        "keywordFilter":            { "query": "Zeppelin" },
+        "sort":                     { "orders": [[direction, "RELEVANCE"]] },
+        "UserHighClassification":   "UNCLASSIFIED",
+    });
+
+    let response = server.client().post(server.url(SEARCH_MESSAGES_ROUTE))
+        .body(request.to_string())
+        .send().await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body: serde_json::Value = serde_json::from_str(&response.text().await.unwrap()).unwrap();
+    body["messages"].as_array().unwrap().iter()
+        .map(|message| String::from(message["text"].as_str().unwrap()))
+        .collect()
+}
+
+#[tokio::test]
+async fn equal_scores_keep_the_oldest_first_in_either_direction() {
+    let server = TestServer::start();
+
+    post_message(&server, "Zeppelin first").await;
+    tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+    post_message(&server, "Zeppelin second").await;
+    tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+    post_message(&server, "Zeppelin after Zeppelin").await;
+
+    assert_eq!(texts_by_relevance(&server, "DESC").await,
+        ["Zeppelin after Zeppelin", "Zeppelin first", "Zeppelin second"]);
+    assert_eq!(texts_by_relevance(&server, "ASC").await,
+        ["Zeppelin first", "Zeppelin second", "Zeppelin after Zeppelin"]);
+}
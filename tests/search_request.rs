@@ -0,0 +1,40 @@
+mod common;
+
+use common::*;
+use reqwest::StatusCode;
+
+/// This function searches with the given request, returning the status.
+async fn search_status(server: &TestServer, request: serde_json::Value) -> StatusCode {
+    server.client().post(server.url(SEARCH_MESSAGES_ROUTE))
+        .body(request.to_string())
+        .send().await.unwrap()
+        .status()
+}
+
+#[tokio::test]
+async fn the_user_classification_may_be_left_out() {
+    let server = TestServer::start();
+
+    let request = serde_json::json!({
+        "keywordFilter":    { "query": "test" },
+    });
+    assert_eq!(search_status(&server, request).await, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn the_user_classification_may_be_camel_case() {
+    let server = TestServer::start();
+
+    let request = serde_json::json!({
+        "keywordFilter":            { "query": "test" },
+        "userHighClassification":   "UNCLASSIFIED",
+    });
+    assert_eq!(search_status(&server, request).await, StatusCode::OK);
+
+    // The camelCase value is used, not the default.
+    let request = serde_json::json!({
+        "keywordFilter":            { "query": "test" },
+        "userHighClassification":   "NOT A CLASSIFICATION",
+    });
+    assert_eq!(search_status(&server, request).await, StatusCode::BAD_REQUEST);
+}
@@ -0,0 +1,36 @@
+mod common;
+
+use common::*;
+use reqwest::StatusCode;
+
+/// This function searches for the test keyword with the given sort orders.
+async fn search_sorted(server: &TestServer, orders: serde_json::Value) -> reqwest::Response {
+    let request = serde_json::json!({
+        "keywordFilter":            { "query": "Antediluvian" },
+        "sort":                     { "orders": orders },
+        "UserHighClassification":   "UNCLASSIFIED",
+    });
+
+    server.client().post(server.url(SEARCH_MESSAGES_ROUTE))
+        .body(request.to_string())
+        .send().await.unwrap()
+}
+
+#[tokio::test]
+async fn duplicate_sort_fields_are_rejected() {
+    let server = TestServer::start();
+
+    let response = search_sorted(&server, serde_json::json!([["ASC", "TIME"], ["DESC", "TIME"]])).await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+    let body: serde_json::Value = serde_json::from_str(&response.text().await.unwrap()).unwrap();
+    assert_eq!(body["fieldErrors"][0]["fieldName"], "sort");
+}
+
+#[tokio::test]
+async fn unique_sort_fields_are_accepted() {
+    let server = TestServer::start();
+
+    let response = search_sorted(&server, serde_json::json!([["ASC", "TIME"], ["DESC", "SENDER"]])).await;
+    assert_eq!(response.status(), StatusCode::OK);
+}
@@ -0,0 +1,79 @@
+mod common;
+
+use common::*;
+use reqwest::StatusCode;
+
+#[tokio::test]
+async fn every_invalid_field_is_reported() {
+    let server = TestServer::start();
+
+    let request = serde_json::json!({
+        "keywordFilter":            { "query": "*diluvian" },
+        "sort":                     { "orders": [["ASC", "TIME"], ["DESC", "TIME"]] },
+        "UserHighClassification":   "UNCLASSIFIED",
+    });
+
+    let response = server.client().post(server.url(SEARCH_MESSAGES_ROUTE))
+        .body(request.to_string())
+        .send().await.unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+    let body: serde_json::Value = serde_json::from_str(&response.text().await.unwrap()).unwrap();
+    assert_eq!(body["message"], "The request contained 1 or more field validation errors.");
+
+    let mut fields: Vec<&str> = body["fieldErrors"].as_array().unwrap().iter()
+        .map(|error| error["fieldName"].as_str().unwrap())
+        .collect();
+    fields.sort();
+    assert_eq!(fields, ["keywordFilter", "sort"]);
+}
+
+/// This function searches with an empty query.
+async fn search_empty_query(server: &TestServer) -> reqwest::Response {
+    let request = serde_json::json!({
+        "keywordFilter":            { "query": "" },
+        "UserHighClassification":   "UNCLASSIFIED",
+    });
+
+    server.client().post(server.url(SEARCH_MESSAGES_ROUTE))
+        .body(request.to_string())
+        .send().await.unwrap()
+}
+
+#[tokio::test]
+async fn empty_queries_are_rejected_by_default() {
+    let server = TestServer::start();
+
+    let response = search_empty_query(&server).await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+    let body: serde_json::Value = serde_json::from_str(&response.text().await.unwrap()).unwrap();
+    assert_eq!(body["fieldErrors"][0]["fieldName"], "keywordFilter");
+}
+
+#[tokio::test]
+async fn empty_queries_can_match_everything() {
+    let server = TestServer::start_with_args(&["--empty_query_behavior", "all"]);
+
+    let response = search_empty_query(&server).await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body: serde_json::Value = serde_json::from_str(&response.text().await.unwrap()).unwrap();
+    assert_eq!(body["total"], 10);
+}
+
+#[tokio::test]
+async fn parse_errors_report_the_field_rather_than_the_request() {
+    let server = TestServer::start();
+
+    let response = server.client().post(server.url(SEARCH_MESSAGES_ROUTE))
+        .body(r#"{"keywordFilter":{"notTheQuery":"a distinctive phrase"}}"#)
+        .send().await.unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+    let body: serde_json::Value = serde_json::from_str(&response.text().await.unwrap()).unwrap();
+    let message = body["message"].as_str().unwrap();
+    assert!(message.contains("query"), "{}", message);
+    assert!(!message.contains("a distinctive phrase"), "{}", message);
+    assert_eq!(body["fieldErrors"][0]["fieldName"], "query");
+}
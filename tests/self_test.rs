@@ -0,0 +1,16 @@
+mod common;
+
+use common::*;
+
+const LOGS_ROUTE: &str = "/api/test/logs";
+
+#[tokio::test]
+async fn the_server_serves_after_passing_its_self_test() {
+    let server = TestServer::start_with_args(&["--self_test", "--enable_admin"]);
+
+    let response = server.client().get(server.url(LOGS_ROUTE))
+        .send().await.unwrap();
+    let records: Vec<serde_json::Value> = serde_json::from_str(&response.text().await.unwrap()).unwrap();
+
+    assert!(records.iter().any(|record| record["message"] == "Self-test passed"));
+}
@@ -0,0 +1,56 @@
+mod common;
+
+use common::*;
+use futures_util::StreamExt;
+use std::time::Duration;
+use tokio_tungstenite::tungstenite::Message;
+
+/// This function returns the messages of the test room.
+async fn get_messages(server: &TestServer) -> Vec<serde_json::Value> {
+    let response = server.client().get(server.url(MESSAGES_ROUTE))
+        .send().await.unwrap();
+    let body: serde_json::Value = serde_json::from_str(&response.text().await.unwrap()).unwrap();
+
+    body["messages"].as_array().unwrap().clone()
+}
+
+#[tokio::test]
+async fn each_sender_always_gets_the_same_color() {
+    let server = TestServer::start_with_args(&["--sender_meta", "--ws_interval_ms", "50"]);
+
+    let messages = get_messages(&server).await;
+    let austin = messages.iter().find(|message| message["sender"] == "Austin").unwrap();
+    let color = austin["senderMeta"]["color"].as_str().unwrap();
+    assert_eq!(color.len(), 7);
+    assert!(color.starts_with('#'));
+
+    // The streamed messages are all from Austin too.
+    let mut stream = server.connect_ws(WS_SINGLE_ROOM_ROUTE).await;
+    let mut streamed = 0;
+
+    while streamed < 3 {
+        let message = tokio::time::timeout(Duration::from_secs(5), stream.next())
+            .await
+            .expect("No message arrived")
+            .unwrap()
+            .unwrap();
+
+        let Message::Text(text) = message else { continue };
+        let body: serde_json::Value = serde_json::from_str(&text).unwrap();
+
+        if body["type"] == "resume" {
+            continue;
+        }
+
+        assert_eq!(body["sender"], "Austin");
+        assert_eq!(body["senderMeta"], austin["senderMeta"]);
+        streamed += 1;
+    }
+}
+
+#[tokio::test]
+async fn sender_meta_is_left_out_by_default() {
+    let server = TestServer::start();
+
+    assert!(get_messages(&server).await.iter().all(|message| message.get("senderMeta").is_none()));
+}
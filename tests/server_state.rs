@@ -0,0 +1,48 @@
+mod common;
+
+use common::*;
+use reqwest::StatusCode;
+use std::time::{
+    Duration,
+    Instant,
+};
+
+const SERVER_STATE_ROUTE: &str = "/api/test/state";
+
+/// This function gets the server's generation state.
+async fn server_state(server: &TestServer) -> serde_json::Value {
+    let response = server.client().get(server.url(SERVER_STATE_ROUTE)).send().await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    serde_json::from_str(&response.text().await.unwrap()).unwrap()
+}
+
+#[tokio::test]
+async fn the_state_reports_the_seed_and_counters() {
+    let server = TestServer::start_with_args(&["--seed", "42"]);
+
+    // The seeded messages are generated when they're first asked for.
+    server.client().get(server.url(MESSAGES_ROUTE)).send().await.unwrap();
+
+    let state = server_state(&server).await;
+    assert_eq!(state["seed"], 42);
+    assert_eq!(state["ws_connections"], 0);
+    assert!(state["messages_generated"].as_u64().unwrap() >= 10);
+    assert!(state["uptime_secs"].is_u64());
+
+    // The connection is counted once it has been upgraded.
+    let _stream = server.connect_ws(WS_SINGLE_ROOM_ROUTE).await;
+    let deadline = Instant::now() + Duration::from_secs(5);
+
+    while server_state(&server).await["ws_connections"] != 1 {
+        assert!(Instant::now() < deadline, "The connection was never counted");
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+}
+
+#[tokio::test]
+async fn the_seed_is_null_when_not_given() {
+    let server = TestServer::start();
+
+    assert!(server_state(&server).await["seed"].is_null());
+}
@@ -0,0 +1,51 @@
+mod common;
+
+use common::*;
+use futures_util::StreamExt;
+use std::time::Duration;
+use tokio::net::TcpStream;
+use tokio_tungstenite::{
+    tungstenite::Message,
+    MaybeTlsStream,
+    WebSocketStream,
+};
+
+/// This function returns the ids of the next chat messages on the stream,
+/// skipping the resume tokens sent alongside them.
+async fn next_ids(stream: &mut WebSocketStream<MaybeTlsStream<TcpStream>>, count: usize) -> Vec<String> {
+    let mut ids = Vec::new();
+
+    while ids.len() < count {
+        let message = tokio::time::timeout(Duration::from_secs(5), stream.next())
+            .await
+            .expect("No message arrived")
+            .unwrap()
+            .unwrap();
+
+        if let Message::Text(text) = message {
+            let body: serde_json::Value = serde_json::from_str(&text).unwrap();
+
+            if body["type"] != "resume" {
+                ids.push(String::from(body["id"].as_str().unwrap()));
+            }
+        }
+    }
+
+    ids
+}
+
+#[tokio::test]
+async fn subscribers_to_a_room_see_the_same_messages() {
+    let server = TestServer::start_with_args(&["--ws_interval_ms", "100"]);
+
+    let mut first = server.connect_ws(WS_SINGLE_ROOM_ROUTE).await;
+    let mut second = server.connect_ws(WS_SINGLE_ROOM_ROUTE).await;
+
+    let (first_ids, second_ids) = tokio::join!(next_ids(&mut first, 6), next_ids(&mut second, 4));
+
+    // The second client may have joined a push or so after the first.
+    let start = first_ids.iter().position(|id| *id == second_ids[0])
+        .expect("The clients saw different messages");
+    assert!(start <= 2, "{:?} {:?}", first_ids, second_ids);
+    assert_eq!(first_ids[start..start + 4], second_ids[..]);
+}
@@ -0,0 +1,27 @@
+mod common;
+
+use common::*;
+use reqwest::StatusCode;
+use std::time::{
+    Duration,
+    Instant,
+};
+
+#[tokio::test]
+async fn responses_are_delayed_within_the_bound() {
+    let server = TestServer::start_with_args(&["--seed", "7", "--shuffle_latency_ms", "300"]);
+    let client = server.client();
+
+    let mut delays = Vec::new();
+
+    for _ in 0..10 {
+        let start = Instant::now();
+        let response = client.get(server.url(GET_API_KEY_ROUTE)).send().await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        delays.push(start.elapsed());
+    }
+
+    // Leave some room over the bound for the request itself.
+    assert!(delays.iter().all(|delay| *delay < Duration::from_millis(300 + 150)), "{:?}", delays);
+    assert!(delays.iter().any(|delay| *delay >= Duration::from_millis(30)), "{:?}", delays);
+}
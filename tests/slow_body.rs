@@ -0,0 +1,41 @@
+mod common;
+
+use common::*;
+use reqwest::StatusCode;
+use std::time::{
+    Duration,
+    Instant,
+};
+
+const SLOW_BODY_ROUTE: &str = "/api/test/slow_body";
+
+#[tokio::test]
+async fn the_body_arrives_slowly_but_whole() {
+    let server = TestServer::start();
+
+    let started = Instant::now();
+    let mut response = server.client()
+        .get(server.url(&format!("{}?chunk_delay_ms=20&chunk_bytes=512", SLOW_BODY_ROUTE)))
+        .send().await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let mut body = Vec::new();
+    let mut chunks = 0;
+
+    while let Some(chunk) = response.chunk().await.unwrap() {
+        body.extend_from_slice(&chunk);
+        chunks += 1;
+    }
+
+    // Every chunk after the first waited before it was sent.
+    let expected_chunks = body.len().div_ceil(512);
+    assert!(chunks > 1);
+    assert!(started.elapsed() >= Duration::from_millis(20 * (expected_chunks as u64 - 1)));
+
+    // The body is the same as the get messages route's.
+    let slow: serde_json::Value = serde_json::from_slice(&body).unwrap();
+
+    let response = server.client().get(server.url(MESSAGES_ROUTE)).send().await.unwrap();
+    let fast: serde_json::Value = serde_json::from_str(&response.text().await.unwrap()).unwrap();
+    assert_eq!(slow["messages"], fast["messages"]);
+}
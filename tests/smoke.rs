@@ -0,0 +1,92 @@
+mod common;
+
+use common::*;
+use futures_util::StreamExt;
+use reqwest::StatusCode;
+use std::time::Duration;
+use tokio_tungstenite::tungstenite::Message;
+
+const SEND_REQUEST: &str = r#"{
+    "classification": "UNCLASSIFIED",
+    "domainId": "chatsurferxmppunclass",
+    "message": "Hello from the smoke test",
+    "nickname": "Tester",
+    "roomName": "edge-view-test-room"
+}"#;
+
+const SEARCH_REQUEST: &str = r#"{
+    "keywordFilter": { "query": "Antediluvian" },
+    "UserHighClassification": "UNCLASSIFIED"
+}"#;
+
+/// This function parses a response body as JSON, failing the test if it
+/// can't be.
+async fn json_body(response: reqwest::Response) -> serde_json::Value {
+    let body = response.text().await.unwrap();
+    serde_json::from_str(&body)
+        .unwrap_or_else(|e| panic!("The body {} is not JSON: {}", body, e))
+}
+
+#[tokio::test]
+async fn get_api_key_returns_a_key() {
+    let server = TestServer::start();
+
+    let response = server.client().get(server.url(GET_API_KEY_ROUTE)).send().await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body = json_body(response).await;
+    assert!(body["key"].is_string());
+}
+
+#[tokio::test]
+async fn get_messages_returns_the_seeded_messages() {
+    let server = TestServer::start();
+
+    let response = server.client().get(server.url(MESSAGES_ROUTE)).send().await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body = json_body(response).await;
+    assert_eq!(body["messages"].as_array().unwrap().len(), 10);
+}
+
+#[tokio::test]
+async fn post_message_returns_no_content() {
+    let server = TestServer::start();
+
+    let response = server.client().post(server.url(NEW_MESSAGE_ROUTE))
+        .body(SEND_REQUEST)
+        .send().await.unwrap();
+    assert_eq!(response.status(), StatusCode::NO_CONTENT);
+    assert!(response.text().await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn search_returns_the_matching_messages() {
+    let server = TestServer::start();
+
+    let response = server.client().post(server.url(SEARCH_MESSAGES_ROUTE))
+        .body(SEARCH_REQUEST)
+        .send().await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body = json_body(response).await;
+    assert_eq!(body["total"], 3);
+}
+
+#[tokio::test]
+async fn websocket_streams_chat_messages() {
+    let server = TestServer::start();
+    let mut stream = server.connect_ws(WS_SINGLE_ROOM_ROUTE).await;
+
+    let message = tokio::time::timeout(Duration::from_secs(5), stream.next())
+        .await
+        .expect("No message arrived")
+        .unwrap()
+        .unwrap();
+
+    let Message::Text(text) = message else {
+        panic!("Expected a text message, got {:?}", message);
+    };
+    let body: serde_json::Value = serde_json::from_str(&text).unwrap();
+    assert_eq!(body["roomName"], "edge-view-test-room");
+}
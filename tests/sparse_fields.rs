@@ -0,0 +1,44 @@
+mod common;
+
+use common::*;
+use futures_util::StreamExt;
+use tokio_tungstenite::tungstenite::Message;
+
+/// This function asserts that the message has neither of the optional
+/// fields the sparse ratio leaves out.
+fn assert_sparse(message: &serde_json::Value) {
+    assert!(message["geoTags"].is_null(), "{}", message);
+    assert!(message["threadId"].is_null(), "{}", message);
+}
+
+#[tokio::test]
+async fn every_message_is_sparse_at_a_ratio_of_one() {
+    let server = TestServer::start_with_args(&["--sparse_fields", "1.0"]);
+
+    let response = server.client().get(server.url(MESSAGES_ROUTE)).send().await.unwrap();
+    let body: serde_json::Value = serde_json::from_str(&response.text().await.unwrap()).unwrap();
+
+    let messages = body["messages"].as_array().unwrap();
+    assert_eq!(messages.len(), 10);
+    messages.iter().for_each(assert_sparse);
+
+    let mut stream = server.connect_ws(WS_SINGLE_ROOM_ROUTE).await;
+
+    let Some(Ok(Message::Text(text))) = stream.next().await else {
+        panic!("No message arrived");
+    };
+    assert_sparse(&serde_json::from_str(&text).unwrap());
+}
+
+#[tokio::test]
+async fn no_message_is_sparse_by_default() {
+    let server = TestServer::start();
+
+    let response = server.client().get(server.url(MESSAGES_ROUTE)).send().await.unwrap();
+    let body: serde_json::Value = serde_json::from_str(&response.text().await.unwrap()).unwrap();
+
+    for message in body["messages"].as_array().unwrap() {
+        assert!(message["geoTags"].is_array());
+        assert!(message["threadId"].is_string());
+    }
+}
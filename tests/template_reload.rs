@@ -0,0 +1,69 @@
+mod common;
+
+use common::*;
+use futures_util::StreamExt;
+use std::{
+    path::Path,
+    time::{
+        Duration,
+        Instant,
+    },
+};
+use tokio_tungstenite::tungstenite::Message;
+
+/// This function opens a new WebSocket connection and returns the sender
+/// of the first chat message on it.
+async fn first_sender(server: &TestServer) -> String {
+    let mut stream = server.connect_ws(WS_SINGLE_ROOM_ROUTE).await;
+
+    let message = tokio::time::timeout(Duration::from_secs(5), stream.next())
+        .await
+        .expect("No message arrived")
+        .unwrap()
+        .unwrap();
+
+    let Message::Text(text) = message else {
+        panic!("Expected a text message, got {:?}", message);
+    };
+    let body: serde_json::Value = serde_json::from_str(&text).unwrap();
+
+    String::from(body["sender"].as_str().unwrap())
+}
+
+/// This function writes a template file with a single template from the
+/// given sender.
+fn write_templates(path: &Path, sender: &str) {
+    let templates = serde_json::json!([{ "sender": sender, "text": "Template text" }]);
+    std::fs::write(path, templates.to_string()).unwrap();
+}
+
+#[tokio::test]
+async fn template_changes_are_picked_up_and_bad_ones_ignored() {
+    let directory = std::env::temp_dir()
+        .join(format!("websocket-echoserver-templates-{}", std::process::id()));
+    std::fs::create_dir_all(&directory).unwrap();
+    let path = directory.join("templates.json");
+
+    write_templates(&path, "Before");
+    let server = TestServer::start_with_args(&[
+        "--template_file", path.to_str().unwrap(),
+        "--ws_interval_ms", "50",
+    ]);
+    assert_eq!(first_sender(&server).await, "Before");
+
+    // The reload happens in the background, so wait for it to land.
+    write_templates(&path, "After");
+    let deadline = Instant::now() + Duration::from_secs(10);
+
+    while first_sender(&server).await != "After" {
+        assert!(Instant::now() < deadline, "The template file was never reloaded");
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+
+    // Content that doesn't parse leaves the last good templates in place.
+    std::fs::write(&path, "not json").unwrap();
+    tokio::time::sleep(Duration::from_millis(500)).await;
+    assert_eq!(first_sender(&server).await, "After");
+
+    let _ = std::fs::remove_dir_all(&directory);
+}
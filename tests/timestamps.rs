@@ -0,0 +1,70 @@
+mod common;
+
+use common::*;
+use reqwest::StatusCode;
+
+/// This function searches every message, within the given time filter.
+/// The server has to be started with `--empty_query_behavior all`.
+async fn search_with_time_filter(
+    server: &TestServer,
+    time_filter: serde_json::Value,
+) -> reqwest::Response {
+    let request = serde_json::json!({
+        "keywordFilter":            { "query": "" },
+        "timeFilter":               time_filter,
+        "UserHighClassification":   "UNCLASSIFIED",
+    });
+
+    server.client().post(server.url(SEARCH_MESSAGES_ROUTE))
+        .body(request.to_string())
+        .send().await.unwrap()
+}
+
+#[tokio::test]
+async fn timestamps_are_rfc3339() {
+    let server = TestServer::start_with_args(&["--empty_query_behavior", "all"]);
+
+    let response = search_with_time_filter(&server, serde_json::json!({})).await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body: serde_json::Value = serde_json::from_str(&response.text().await.unwrap()).unwrap();
+    let messages = body["messages"].as_array().unwrap();
+    assert!(!messages.is_empty());
+
+    for message in messages {
+        let timestamp = message["timestamp"].as_str().unwrap();
+        assert!(chrono::DateTime::parse_from_rfc3339(timestamp).is_ok(), "{}", timestamp);
+    }
+}
+
+#[tokio::test]
+async fn invalid_timestamps_are_rejected() {
+    let server = TestServer::start_with_args(&["--empty_query_behavior", "all"]);
+
+    let response = search_with_time_filter(&server, serde_json::json!({
+        "startDateTime": "yesterday",
+    })).await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+    let body: serde_json::Value = serde_json::from_str(&response.text().await.unwrap()).unwrap();
+    assert!(body["message"].as_str().unwrap().contains("invalid timestamp \"yesterday\""));
+}
+
+#[tokio::test]
+async fn time_filters_bound_the_results() {
+    let server = TestServer::start_with_args(&["--empty_query_behavior", "all"]);
+
+    // The legacy format is still accepted.
+    let response = search_with_time_filter(&server, serde_json::json!({
+        "startDateTime": "2000-01-01 00:00:00.000 UTC",
+        "endDateTime":   "",
+    })).await;
+    let body: serde_json::Value = serde_json::from_str(&response.text().await.unwrap()).unwrap();
+    assert_eq!(body["total"], 10);
+
+    let response = search_with_time_filter(&server, serde_json::json!({
+        "startDateTime": "2999-01-01T00:00:00Z",
+    })).await;
+    let body: serde_json::Value = serde_json::from_str(&response.text().await.unwrap()).unwrap();
+    assert_eq!(body["total"], 0);
+}
@@ -0,0 +1,104 @@
+mod common;
+
+use common::*;
+use jsonwebtoken::{
+    Algorithm,
+    DecodingKey,
+    Validation,
+};
+use reqwest::StatusCode;
+
+const PUBLIC_KEY_ROUTE: &str = "/auth/realms/fmv";
+const TOKEN_ROUTE: &str = "/keycloak/token";
+const OTHER_TOKEN_KEY_FILE: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/other_token_key.pem");
+
+/// This function returns the key that verifies the server's tokens, built
+/// from its published public key the way a client would.
+async fn published_key(server: &TestServer) -> DecodingKey {
+    let response = server.client().get(server.url(PUBLIC_KEY_ROUTE)).send().await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body: serde_json::Value = serde_json::from_str(&response.text().await.unwrap()).unwrap();
+    let pem = format!(
+        "-----BEGIN PUBLIC KEY-----\n{}\n-----END PUBLIC KEY-----",
+        body["public_key"].as_str().unwrap());
+
+    DecodingKey::from_rsa_pem(pem.as_bytes()).unwrap()
+}
+
+/// This function requests a token with the given body, returning the
+/// access token.
+async fn issue(server: &TestServer, body: &str) -> String {
+    let response = server.client().post(server.url(TOKEN_ROUTE))
+        .body(body.to_string())
+        .send().await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body: serde_json::Value = serde_json::from_str(&response.text().await.unwrap()).unwrap();
+    assert_eq!(body["token_type"], "Bearer");
+    String::from(body["access_token"].as_str().unwrap())
+}
+
+#[tokio::test]
+async fn tokens_verify_against_the_published_key() {
+    let server = TestServer::start();
+
+    let token = issue(&server, "").await;
+    let key = published_key(&server).await;
+
+    let claims = jsonwebtoken::decode::<serde_json::Value>(&token, &key, &Validation::new(Algorithm::RS256))
+        .unwrap()
+        .claims;
+    assert_eq!(claims["sub"], "Austin");
+    assert_eq!(claims["realm"], "fmv");
+    assert_eq!(claims["exp"].as_u64().unwrap() - claims["iat"].as_u64().unwrap(), 300);
+}
+
+#[tokio::test]
+async fn the_claims_can_be_configured() {
+    let server = TestServer::start();
+
+    let token = issue(&server, r#"{"sub":"Pilot","realm":"ops","expires_in":60}"#).await;
+    let key = published_key(&server).await;
+
+    let claims = jsonwebtoken::decode::<serde_json::Value>(&token, &key, &Validation::new(Algorithm::RS256))
+        .unwrap()
+        .claims;
+    assert_eq!(claims["sub"], "Pilot");
+    assert_eq!(claims["realm"], "ops");
+    assert_eq!(claims["exp"].as_u64().unwrap() - claims["iat"].as_u64().unwrap(), 60);
+}
+
+#[tokio::test]
+async fn tokens_do_not_verify_against_another_servers_key() {
+    let server = TestServer::start();
+    let other = TestServer::start_with_args(&["--token_key_file", OTHER_TOKEN_KEY_FILE]);
+
+    let token = issue(&server, "").await;
+    let key = published_key(&other).await;
+
+    assert!(jsonwebtoken::decode::<serde_json::Value>(&token, &key, &Validation::new(Algorithm::RS256)).is_err());
+}
+
+#[tokio::test]
+async fn servers_with_the_same_key_file_publish_the_same_key() {
+    let public_key = |server: TestServer| async move {
+        let response = server.client().get(server.url(PUBLIC_KEY_ROUTE)).send().await.unwrap();
+        let body: serde_json::Value = serde_json::from_str(&response.text().await.unwrap()).unwrap();
+        String::from(body["public_key"].as_str().unwrap())
+    };
+
+    let first = public_key(TestServer::start_with_args(&["--token_key_file", OTHER_TOKEN_KEY_FILE])).await;
+    let second = public_key(TestServer::start_with_args(&["--token_key_file", OTHER_TOKEN_KEY_FILE])).await;
+    assert_eq!(first, second);
+}
+
+#[tokio::test]
+async fn malformed_token_requests_are_refused() {
+    let server = TestServer::start();
+
+    let response = server.client().post(server.url(TOKEN_ROUTE))
+        .body(r#"{"expires_in":"soon"}"#)
+        .send().await.unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
@@ -0,0 +1,35 @@
+mod common;
+
+use common::*;
+use reqwest::StatusCode;
+
+/// This function gets the given route, returning the response body.
+async fn get(server: &TestServer, route: &str) -> serde_json::Value {
+    let response = server.client().get(server.url(route)).send().await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    serde_json::from_str(&response.text().await.unwrap()).unwrap()
+}
+
+#[tokio::test]
+async fn a_trailing_slash_gets_the_same_messages() {
+    let server = TestServer::start();
+
+    let without = get(&server, MESSAGES_ROUTE).await;
+    let with = get(&server, &format!("{}/", MESSAGES_ROUTE)).await;
+    assert_eq!(without["messages"], with["messages"]);
+    assert!(!with["messages"].as_array().unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn a_trailing_slash_reaches_the_search_route() {
+    let server = TestServer::start_with_args(&["--empty_query_behavior", "all"]);
+
+    let response = server.client().post(server.url(&format!("{}/", SEARCH_MESSAGES_ROUTE)))
+        .body(r#"{"keywordFilter":{"query":""},"userHighClassification":"UNCLASSIFIED"}"#)
+        .send().await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body: serde_json::Value = serde_json::from_str(&response.text().await.unwrap()).unwrap();
+    assert_eq!(body["total"], 10);
+}
@@ -0,0 +1,20 @@
+mod common;
+
+use common::*;
+
+#[tokio::test]
+async fn unicode_samples_round_trip_as_utf8() {
+    let server = TestServer::start_with_args(&["--unicode_sample"]);
+
+    let response = server.client().get(server.url(MESSAGES_ROUTE))
+        .send().await.unwrap();
+    let body: serde_json::Value = serde_json::from_str(&response.text().await.unwrap()).unwrap();
+
+    let messages = body["messages"].as_array().unwrap();
+    assert!(!messages.is_empty());
+
+    for message in messages {
+        assert!(!message["sender"].as_str().unwrap().is_ascii());
+        assert!(!message["text"].as_str().unwrap().is_ascii());
+    }
+}
@@ -0,0 +1,32 @@
+#![cfg(unix)]
+
+mod common;
+
+use common::*;
+use tokio::{
+    io::{
+        AsyncReadExt,
+        AsyncWriteExt,
+    },
+    net::UnixStream,
+};
+
+#[tokio::test]
+async fn messages_are_served_over_a_unix_socket() {
+    let path = std::env::temp_dir()
+        .join(format!("websocket-echoserver-{}.sock", std::process::id()));
+    let _server = TestServer::start_with_args(&["--uds_path", path.to_str().unwrap()]);
+
+    let mut stream = UnixStream::connect(&path).await.unwrap();
+    stream.write_all(format!(
+        "GET {} HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n",
+        MESSAGES_ROUTE).as_bytes()).await.unwrap();
+
+    let mut response = String::new();
+    stream.read_to_string(&mut response).await.unwrap();
+
+    assert!(response.starts_with("HTTP/1.1 200 OK"));
+    assert!(response.contains("\"roomName\":\"edge-view-test-room\""));
+
+    let _ = std::fs::remove_file(&path);
+}
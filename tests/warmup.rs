@@ -0,0 +1,38 @@
+mod common;
+
+use common::*;
+use reqwest::{
+    header::RETRY_AFTER,
+    StatusCode,
+};
+
+#[tokio::test]
+async fn data_routes_succeed_after_the_warmup_failures() {
+    let server = TestServer::start_with_args(&["--warmup_failures", "2"]);
+
+    for _ in 0..2 {
+        let response = server.client().get(server.url(MESSAGES_ROUTE)).send().await.unwrap();
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+
+        let retry_after: u64 = response.headers()[RETRY_AFTER].to_str().unwrap().parse().unwrap();
+        assert!((1..=3).contains(&retry_after));
+    }
+
+    let response = server.client().get(server.url(MESSAGES_ROUTE)).send().await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+}
+
+#[tokio::test]
+async fn warmup_failures_are_counted_per_route() {
+    let server = TestServer::start_with_args(&["--warmup_failures", "1"]);
+
+    let response = server.client().get(server.url(MESSAGES_ROUTE)).send().await.unwrap();
+    assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+
+    // Another route still has its own failure to serve.
+    let response = server.client().get(server.url(GET_API_KEY_ROUTE)).send().await.unwrap();
+    assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+
+    let response = server.client().get(server.url(GET_API_KEY_ROUTE)).send().await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+}
@@ -0,0 +1,26 @@
+mod common;
+
+use common::*;
+use reqwest::StatusCode;
+use tokio_tungstenite::tungstenite::Error;
+
+#[tokio::test]
+async fn upgrades_without_the_key_are_refused() {
+    let server = TestServer::start_with_args(&["--require_ws_auth"]);
+
+    let result = tokio_tungstenite::connect_async(
+        format!("ws://{}{}", server.address, WS_SINGLE_ROOM_ROUTE)).await;
+
+    match result {
+        Err(Error::Http(response)) => assert_eq!(response.status(), StatusCode::UNAUTHORIZED.as_u16()),
+        Err(e) => panic!("Expected a 401, got {}", e),
+        Ok(_) => panic!("The upgrade should have been refused"),
+    }
+}
+
+#[tokio::test]
+async fn upgrades_with_the_key_succeed() {
+    let server = TestServer::start_with_args(&["--require_ws_auth"]);
+
+    let _stream = server.connect_ws(&format!("{}?token=a7B5siy9xY1dmN", WS_SINGLE_ROOM_ROUTE)).await;
+}
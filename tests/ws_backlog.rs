@@ -0,0 +1,50 @@
+mod common;
+
+use common::*;
+use futures_util::StreamExt;
+use std::time::Duration;
+use tokio_tungstenite::tungstenite::Message;
+
+const INJECT_ROUTE: &str = "/api/test/inject";
+
+#[tokio::test]
+async fn the_backlog_arrives_before_the_live_stream() {
+    let server = TestServer::start();
+
+    // Base the injected messages on a generated one.
+    let response = server.client().get(server.url(MESSAGES_ROUTE))
+        .send().await.unwrap();
+    let body: serde_json::Value = serde_json::from_str(&response.text().await.unwrap()).unwrap();
+    let template = body["messages"][0].clone();
+
+    for index in 1..=5 {
+        let mut message = template.clone();
+        message["id"] = serde_json::json!(format!("backlog-{}", index));
+        message["text"] = serde_json::json!(format!("Backlog message {}", index));
+
+        server.client().post(server.url(INJECT_ROUTE))
+            .body(message.to_string())
+            .send().await.unwrap();
+    }
+
+    let mut stream = server.connect_ws(&format!("{}?backlog=3", WS_SINGLE_ROOM_ROUTE)).await;
+    let mut texts = Vec::new();
+
+    while texts.len() < 3 {
+        let message = tokio::time::timeout(Duration::from_secs(5), stream.next())
+            .await
+            .expect("No message arrived")
+            .unwrap()
+            .unwrap();
+
+        if let Message::Text(text) = message {
+            let body: serde_json::Value = serde_json::from_str(&text).unwrap();
+
+            if body["type"] != "resume" {
+                texts.push(String::from(body["text"].as_str().unwrap()));
+            }
+        }
+    }
+
+    assert_eq!(texts, ["Backlog message 3", "Backlog message 4", "Backlog message 5"]);
+}
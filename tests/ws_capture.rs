@@ -0,0 +1,47 @@
+mod common;
+
+use common::*;
+use futures_util::SinkExt;
+use std::time::{
+    Duration,
+    Instant,
+};
+use tokio_tungstenite::tungstenite::Message;
+
+#[tokio::test]
+async fn received_frames_are_captured() {
+    let directory = std::env::temp_dir()
+        .join(format!("websocket-echoserver-capture-{}", std::process::id()));
+    std::fs::create_dir_all(&directory).unwrap();
+    let path = directory.join("capture.jsonl");
+
+    let server = TestServer::start_with_args(&["--ws_capture_file", path.to_str().unwrap()]);
+    let mut stream = server.connect_ws(WS_SINGLE_ROOM_ROUTE).await;
+
+    stream.send(Message::Text(String::from("hello"))).await.unwrap();
+    stream.send(Message::Binary(vec![0xca, 0xfe])).await.unwrap();
+
+    // The capture is flushed periodically, so wait for both lines.
+    let deadline = Instant::now() + Duration::from_secs(10);
+    let lines: Vec<serde_json::Value> = loop {
+        let contents = std::fs::read_to_string(&path).unwrap_or_default();
+        let lines: Vec<serde_json::Value> = contents.lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+
+        if lines.len() == 2 {
+            break lines;
+        }
+        assert!(Instant::now() < deadline, "The frames were never captured");
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    };
+
+    assert_eq!(lines[0]["type"], "text");
+    assert_eq!(lines[0]["payload"], "hello");
+    assert_eq!(lines[1]["type"], "binary");
+    assert_eq!(lines[1]["payload"], "cafe");
+    assert_eq!(lines[0]["connectionId"], lines[1]["connectionId"]);
+    assert!(lines[0]["timestamp"].is_string());
+
+    let _ = std::fs::remove_dir_all(&directory);
+}
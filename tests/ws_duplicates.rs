@@ -0,0 +1,52 @@
+mod common;
+
+use common::*;
+use futures_util::StreamExt;
+use std::time::Duration;
+use tokio::net::TcpStream;
+use tokio_tungstenite::{
+    tungstenite::Message,
+    MaybeTlsStream,
+    WebSocketStream,
+};
+
+/// This function waits for the next chat message on the stream, skipping
+/// the resume tokens sent alongside them.
+async fn next_chat_message(stream: &mut WebSocketStream<MaybeTlsStream<TcpStream>>) -> serde_json::Value {
+    loop {
+        let message = tokio::time::timeout(Duration::from_secs(5), stream.next())
+            .await
+            .expect("No message arrived")
+            .unwrap()
+            .unwrap();
+
+        if let Message::Text(text) = message {
+            let body: serde_json::Value = serde_json::from_str(&text).unwrap();
+
+            if body["type"] != "resume" {
+                return body;
+            }
+        }
+    }
+}
+
+#[tokio::test]
+async fn every_message_is_sent_twice() {
+    let server = TestServer::start_with_args(&["--ws_dup_rate", "1.0", "--ws_interval_ms", "50"]);
+    let mut stream = server.connect_ws(WS_SINGLE_ROOM_ROUTE).await;
+
+    let mut ids = Vec::new();
+
+    for _ in 0..3 {
+        let first = next_chat_message(&mut stream).await;
+        let second = next_chat_message(&mut stream).await;
+
+        assert_eq!(first["id"], second["id"]);
+        assert_eq!(first["sequence"], second["sequence"]);
+        ids.push(first["id"].clone());
+    }
+
+    // Each pair is a different message.
+    assert_ne!(ids[0], ids[1]);
+    assert_ne!(ids[1], ids[2]);
+}
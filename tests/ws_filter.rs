@@ -0,0 +1,85 @@
+mod common;
+
+use common::*;
+use futures_util::StreamExt;
+use std::time::Duration;
+use tokio_tungstenite::tungstenite::{
+    protocol::frame::coding::CloseCode,
+    Message,
+};
+
+/// This function posts a message with the given text and nickname to the
+/// test room.
+async fn post_as(server: &TestServer, nickname: &str, text: &str) {
+    let request = serde_json::json!({
+        "classification":   "UNCLASSIFIED",
+        "domainId":         "chatsurferxmppunclass",
+        "message":          text,
+        "nickname":         nickname,
+        "roomName":         "edge-view-test-room",
+    });
+
+    let response = server.client().post(server.url(NEW_MESSAGE_ROUTE))
+        .body(request.to_string())
+        .send().await.unwrap();
+    assert!(response.status().is_success());
+}
+
+/// This function returns the senders and texts of the chat messages that
+/// arrive on a stream with the given filter before it goes quiet.
+async fn filtered_messages(server: &TestServer, filter: &str) -> Vec<(String, String)> {
+    let mut stream = server.connect_ws(&format!("{}?filter={}", WS_SINGLE_ROOM_ROUTE, filter)).await;
+
+    post_as(server, "Pilot", "Zeppelin sighted").await;
+    post_as(server, "Tester", "Zeppelin landed").await;
+    post_as(server, "Tester", "Nothing to report").await;
+
+    let mut messages = Vec::new();
+
+    while let Ok(Some(message)) = tokio::time::timeout(Duration::from_secs(1), stream.next()).await {
+        if let Message::Text(text) = message.unwrap() {
+            let body: serde_json::Value = serde_json::from_str(&text).unwrap();
+
+            if let (Some(sender), Some(text)) = (body["sender"].as_str(), body["text"].as_str()) {
+                messages.push((String::from(sender), String::from(text)));
+            }
+        }
+    }
+
+    messages
+}
+
+#[tokio::test]
+async fn equality_filters_match_the_whole_field() {
+    let server = TestServer::start();
+
+    let messages = filtered_messages(&server, "sender=Pilot").await;
+    assert_eq!(messages, [(String::from("Pilot"), String::from("Zeppelin sighted"))]);
+}
+
+#[tokio::test]
+async fn contains_filters_match_part_of_the_field() {
+    let server = TestServer::start();
+
+    let messages = filtered_messages(&server, "text~Zeppelin").await;
+    let texts: Vec<&str> = messages.iter().map(|(_, text)| text.as_str()).collect();
+    assert_eq!(texts, ["Zeppelin sighted", "Zeppelin landed"]);
+}
+
+#[tokio::test]
+async fn unsupported_filters_close_the_connection() {
+    let server = TestServer::start();
+    let mut stream = server.connect_ws(&format!("{}?filter=colour=blue", WS_SINGLE_ROOM_ROUTE)).await;
+
+    let message = tokio::time::timeout(Duration::from_secs(5), stream.next())
+        .await
+        .expect("The connection wasn't closed")
+        .unwrap()
+        .unwrap();
+
+    let Message::Close(Some(frame)) = message else {
+        panic!("Expected a close frame, got {:?}", message);
+    };
+    assert_eq!(frame.code, CloseCode::Policy);
+    assert!(frame.reason.contains("Unknown filter field"), "{}", frame.reason);
+}
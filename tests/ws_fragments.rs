@@ -0,0 +1,130 @@
+mod common;
+
+use common::*;
+use futures_util::{
+    SinkExt,
+    StreamExt,
+};
+use std::{
+    collections::HashMap,
+    time::{
+        Duration,
+        Instant,
+    },
+};
+use tokio_tungstenite::tungstenite::{
+    protocol::frame::{
+        coding::{
+            Data,
+            OpCode,
+        },
+        Frame,
+    },
+    error::ProtocolError,
+    Error,
+    Message,
+};
+
+/// This function returns a frame carrying part of a text message.  The
+/// first part is sent as text, and the rest as continuations of it.
+fn text_fragment(data: &str, first: bool, last: bool) -> Message {
+    let opcode = if first { Data::Text } else { Data::Continue };
+
+    Message::Frame(Frame::message(data.as_bytes().to_vec(), OpCode::Data(opcode), last))
+}
+
+#[tokio::test]
+async fn large_messages_can_be_reassembled() {
+    let server = TestServer::start_with_args(&["--ws_fragment_bytes", "64"]);
+    let mut stream = server.connect_ws(WS_SINGLE_ROOM_ROUTE).await;
+
+    // Collect fragments until every one of a message has arrived.
+    let mut fragments: HashMap<String, Vec<serde_json::Value>> = HashMap::new();
+
+    let (id, mut parts) = loop {
+        let message = tokio::time::timeout(Duration::from_secs(5), stream.next())
+            .await
+            .expect("No message arrived")
+            .unwrap()
+            .unwrap();
+
+        let Message::Text(text) = message else { continue };
+        let fragment: serde_json::Value = serde_json::from_str(&text).unwrap();
+
+        // Skip the resume tokens.
+        if fragment.get("count").is_none() {
+            continue;
+        }
+        assert!(fragment["data"].as_str().unwrap().len() <= 64);
+
+        let id = String::from(fragment["id"].as_str().unwrap());
+        let count = fragment["count"].as_u64().unwrap() as usize;
+        let parts = fragments.entry(id.clone()).or_default();
+        parts.push(fragment);
+
+        if parts.len() == count {
+            let parts = fragments.remove(&id).unwrap();
+            break (id, parts);
+        }
+    };
+
+    parts.sort_by_key(|fragment| fragment["index"].as_u64().unwrap());
+    let json: String = parts.iter()
+        .map(|fragment| fragment["data"].as_str().unwrap())
+        .collect();
+
+    let message: serde_json::Value = serde_json::from_str(&json).unwrap();
+    assert_eq!(message["id"], id.as_str());
+    assert!(message["text"].is_string());
+}
+
+#[tokio::test]
+async fn fragmented_client_messages_are_reassembled() {
+    let directory = std::env::temp_dir()
+        .join(format!("websocket-echoserver-fragments-{}", std::process::id()));
+    std::fs::create_dir_all(&directory).unwrap();
+    let path = directory.join("capture.jsonl");
+
+    let server = TestServer::start_with_args(&["--ws_capture_file", path.to_str().unwrap()]);
+    let mut stream = server.connect_ws(WS_SINGLE_ROOM_ROUTE).await;
+
+    stream.send(text_fragment("hello, ", true, false)).await.unwrap();
+    stream.send(text_fragment("fragmented ", false, false)).await.unwrap();
+    stream.send(text_fragment("world", false, true)).await.unwrap();
+
+    // The capture is flushed periodically, so wait for the message.
+    let deadline = Instant::now() + Duration::from_secs(10);
+    let line: serde_json::Value = loop {
+        let contents = std::fs::read_to_string(&path).unwrap_or_default();
+
+        if let Some(line) = contents.lines().next() {
+            break serde_json::from_str(line).unwrap();
+        }
+        assert!(Instant::now() < deadline, "The message was never captured");
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    };
+
+    assert_eq!(line["type"], "text");
+    assert_eq!(line["payload"], "hello, fragmented world");
+
+    let _ = std::fs::remove_dir_all(&directory);
+}
+
+#[tokio::test]
+async fn oversize_reassembled_messages_end_the_connection() {
+    // Each fragment fits the limit, but together they don't.
+    let server = TestServer::start_with_args(&["--ws_max_message_bytes", "16"]);
+    let mut stream = server.connect_ws(WS_SINGLE_ROOM_ROUTE).await;
+
+    stream.send(text_fragment("0123456789", true, false)).await.unwrap();
+    stream.send(text_fragment("0123456789", false, true)).await.unwrap();
+
+    let received = tokio::time::timeout(Duration::from_secs(5), stream.next())
+        .await
+        .expect("The connection stayed open");
+
+    // The server drops the connection rather than closing it cleanly.
+    assert!(
+        matches!(received, Some(Err(Error::Protocol(ProtocolError::ResetWithoutClosingHandshake)))),
+        "Received {:?} after an oversize message", received);
+}
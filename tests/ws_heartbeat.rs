@@ -0,0 +1,68 @@
+mod common;
+
+use common::*;
+use futures_util::StreamExt;
+use std::time::Duration;
+use tokio::net::TcpStream;
+use tokio_tungstenite::{
+    tungstenite::Message,
+    MaybeTlsStream,
+    WebSocketStream,
+};
+
+/// This function reads the stream for the given time, returning the
+/// heartbeat frames and the number of chat messages among them.
+async fn read_for(
+    stream:     &mut WebSocketStream<MaybeTlsStream<TcpStream>>,
+    duration:   Duration,
+) -> (Vec<serde_json::Value>, usize) {
+    let mut heartbeats = Vec::new();
+    let mut chat_messages = 0;
+
+    let deadline = tokio::time::Instant::now() + duration;
+
+    while let Ok(Some(message)) = tokio::time::timeout_at(deadline, stream.next()).await {
+        if let Message::Text(text) = message.unwrap() {
+            let body: serde_json::Value = serde_json::from_str(&text).unwrap();
+
+            match body["type"].as_str() {
+                Some("heartbeat") => heartbeats.push(body),
+                Some(_) => (),
+                None => chat_messages += 1,
+            }
+        }
+    }
+
+    (heartbeats, chat_messages)
+}
+
+#[tokio::test]
+async fn heartbeats_are_interleaved_when_asked_for() {
+    let server = TestServer::start_with_args(&[
+        "--heartbeat_interval_secs", "1",
+    ]);
+    let mut stream = server.connect_ws(&format!("{}?heartbeat=true", WS_SINGLE_ROOM_ROUTE)).await;
+
+    let (heartbeats, chat_messages) = read_for(&mut stream, Duration::from_millis(1500)).await;
+
+    assert!(chat_messages > 0);
+    assert_eq!(heartbeats.len(), 2, "{:?}", heartbeats);
+
+    for (seq, heartbeat) in heartbeats.iter().enumerate() {
+        assert_eq!(heartbeat["seq"], seq);
+        assert!(chrono::DateTime::parse_from_rfc3339(heartbeat["ts"].as_str().unwrap()).is_ok());
+    }
+}
+
+#[tokio::test]
+async fn heartbeats_are_not_sent_by_default() {
+    let server = TestServer::start_with_args(&[
+        "--heartbeat_interval_secs", "1",
+    ]);
+    let mut stream = server.connect_ws(WS_SINGLE_ROOM_ROUTE).await;
+
+    let (heartbeats, chat_messages) = read_for(&mut stream, Duration::from_millis(1500)).await;
+
+    assert!(chat_messages > 0);
+    assert!(heartbeats.is_empty());
+}
@@ -0,0 +1,44 @@
+mod common;
+
+use common::*;
+use futures_util::{
+    SinkExt,
+    StreamExt,
+};
+use std::time::Duration;
+use tokio_tungstenite::tungstenite::Message;
+
+#[tokio::test]
+async fn clients_flooding_the_server_are_warned_then_closed() {
+    let server = TestServer::start_with_args(&["--ws_inbound_rate", "5"]);
+    let mut stream = server.connect_ws(WS_SINGLE_ROOM_ROUTE).await;
+
+    // The server may close the connection before every frame is sent.
+    for index in 0..20 {
+        if stream.send(Message::Text(format!("frame {}", index))).await.is_err() {
+            break;
+        }
+    }
+
+    let mut warned = false;
+
+    let code = loop {
+        let message = tokio::time::timeout(Duration::from_secs(5), stream.next())
+            .await
+            .expect("The connection wasn't closed")
+            .unwrap()
+            .unwrap();
+
+        match message {
+            Message::Text(text) => {
+                let body: serde_json::Value = serde_json::from_str(&text).unwrap();
+                warned |= body["type"] == "error";
+            }
+            Message::Close(frame) => break frame.map_or(0, |frame| u16::from(frame.code)),
+            _ => {}
+        }
+    };
+
+    assert!(warned);
+    assert_eq!(code, 1008);
+}
@@ -0,0 +1,89 @@
+mod common;
+
+use common::*;
+use futures_util::StreamExt;
+use reqwest::StatusCode;
+use std::time::{
+    Duration,
+    Instant,
+};
+use tokio::net::TcpStream;
+use tokio_tungstenite::{
+    tungstenite::Message,
+    MaybeTlsStream,
+    WebSocketStream,
+};
+
+const WS_INTERVAL_ROUTE: &str = "/api/test/ws_interval";
+
+/// This function waits for the next chat message on the stream, skipping
+/// the resume tokens sent alongside them.
+async fn next_chat_message(stream: &mut WebSocketStream<MaybeTlsStream<TcpStream>>) {
+    loop {
+        let message = tokio::time::timeout(Duration::from_secs(5), stream.next())
+            .await
+            .expect("No message arrived")
+            .unwrap()
+            .unwrap();
+
+        if let Message::Text(text) = message {
+            let body: serde_json::Value = serde_json::from_str(&text).unwrap();
+
+            if body["type"] != "resume" {
+                return;
+            }
+        }
+    }
+}
+
+#[tokio::test]
+async fn the_interval_changes_mid_stream() {
+    let server = TestServer::start_with_args(&["--enable_admin", "--ws_interval_ms", "1000"]);
+    let mut stream = server.connect_ws(WS_SINGLE_ROOM_ROUTE).await;
+
+    next_chat_message(&mut stream).await;
+
+    let response = server.client().post(server.url(WS_INTERVAL_ROUTE))
+        .body(r#"{"interval_ms": 50}"#)
+        .send().await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.text().await.unwrap(), r#"{"interval_ms":50}"#);
+
+    // The change applies from the next message on, after which the
+    // following messages arrive well within the old interval.
+    next_chat_message(&mut stream).await;
+
+    let started = Instant::now();
+
+    for _ in 0..5 {
+        next_chat_message(&mut stream).await;
+    }
+    assert!(started.elapsed() < Duration::from_millis(1000));
+}
+
+#[tokio::test]
+async fn the_interval_is_fixed_without_admin() {
+    let server = TestServer::start();
+
+    let response = server.client().post(server.url(WS_INTERVAL_ROUTE))
+        .body(r#"{"interval_ms": 50}"#)
+        .send().await.unwrap();
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+}
+
+#[tokio::test]
+async fn invalid_intervals_report_only_the_field() {
+    let server = TestServer::start_with_args(&["--enable_admin"]);
+
+    let response = server.client().post(server.url(WS_INTERVAL_ROUTE))
+        .body(r#"{"interval_ms": 0, "padding": "a distinctive phrase"}"#)
+        .send().await.unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+    let text = response.text().await.unwrap();
+    assert!(!text.contains("a distinctive phrase"), "{}", text);
+
+    let body: serde_json::Value = serde_json::from_str(&text).unwrap();
+    assert_eq!(body["fieldErrors"][0]["fieldName"], "interval_ms");
+    assert_eq!(body["fieldErrors"][0]["rejectedValue"], "0");
+}
@@ -0,0 +1,86 @@
+mod common;
+
+use common::*;
+use futures_util::{
+    SinkExt,
+    StreamExt,
+};
+use std::time::Duration;
+use tokio_tungstenite::tungstenite::{
+    error::ProtocolError,
+    Error,
+    Message,
+};
+
+/// The arguments limiting frames received from clients to 32 bytes.
+const FRAME_LIMIT_ARGS: [&str; 4] = ["--ws_max_frame_bytes", "32", "--ws_interval_ms", "60000"];
+
+#[tokio::test]
+async fn oversize_frames_end_the_connection() {
+    let server = TestServer::start_with_args(&FRAME_LIMIT_ARGS);
+    let mut stream = server.connect_ws(WS_SINGLE_ROOM_ROUTE).await;
+
+    stream.send(Message::Text("x".repeat(100))).await.unwrap();
+
+    let received = tokio::time::timeout(Duration::from_secs(5), stream.next())
+        .await
+        .expect("The connection stayed open");
+
+    // The server drops the connection rather than closing it cleanly.
+    assert!(
+        matches!(received, Some(Err(Error::Protocol(ProtocolError::ResetWithoutClosingHandshake)))),
+        "Received {:?} after an oversize frame", received);
+}
+
+#[tokio::test]
+async fn frames_within_the_limit_are_accepted() {
+    let server = TestServer::start_with_args(&FRAME_LIMIT_ARGS);
+    let mut stream = server.connect_ws(WS_SINGLE_ROOM_ROUTE).await;
+
+    stream.send(Message::Text("x".repeat(16))).await.unwrap();
+    stream.send(Message::Ping(b"still here".to_vec())).await.unwrap();
+
+    let received = tokio::time::timeout(Duration::from_secs(5), stream.next())
+        .await
+        .expect("No pong arrived")
+        .unwrap()
+        .unwrap();
+    assert_eq!(received, Message::Pong(b"still here".to_vec()));
+}
+
+#[tokio::test]
+async fn oversize_outbound_messages_are_skipped() {
+    // Every generated chat message is longer than 32 bytes.
+    let server = TestServer::start_with_args(&["--ws_max_frame_bytes", "32"]);
+    let mut stream = server.connect_ws(WS_SINGLE_ROOM_ROUTE).await;
+    let deadline = tokio::time::Instant::now() + Duration::from_secs(3);
+
+    while let Ok(Some(message)) = tokio::time::timeout_at(deadline, stream.next()).await {
+        if let Message::Text(text) = message.unwrap() {
+            let body: serde_json::Value = serde_json::from_str(&text).unwrap();
+            assert!(body["text"].is_null(), "Received {} despite the frame limit", text);
+        }
+    }
+}
+
+#[tokio::test]
+async fn outbound_messages_within_the_limit_are_sent() {
+    let server = TestServer::start_with_args(&["--ws_max_frame_bytes", "65536"]);
+    let mut stream = server.connect_ws(WS_SINGLE_ROOM_ROUTE).await;
+
+    loop {
+        let message = tokio::time::timeout(Duration::from_secs(5), stream.next())
+            .await
+            .expect("No chat message arrived")
+            .unwrap()
+            .unwrap();
+
+        if let Message::Text(text) = message {
+            let body: serde_json::Value = serde_json::from_str(&text).unwrap();
+
+            if body["text"].is_string() {
+                break;
+            }
+        }
+    }
+}
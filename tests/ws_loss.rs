@@ -0,0 +1,59 @@
+mod common;
+
+use common::*;
+use futures_util::StreamExt;
+use std::time::Duration;
+use tokio::net::TcpStream;
+use tokio_tungstenite::{
+    tungstenite::Message,
+    MaybeTlsStream,
+    WebSocketStream,
+};
+
+/// This function returns the sequence numbers of the next chat messages
+/// on the stream, skipping the resume tokens sent alongside them.
+async fn next_sequences(stream: &mut WebSocketStream<MaybeTlsStream<TcpStream>>, count: usize) -> Vec<u64> {
+    let mut sequences = Vec::new();
+
+    while sequences.len() < count {
+        let message = tokio::time::timeout(Duration::from_secs(5), stream.next())
+            .await
+            .expect("No message arrived")
+            .unwrap()
+            .unwrap();
+
+        if let Message::Text(text) = message {
+            let body: serde_json::Value = serde_json::from_str(&text).unwrap();
+
+            if body["type"] != "resume" {
+                sequences.push(body["sequence"].as_u64().unwrap());
+            }
+        }
+    }
+
+    sequences
+}
+
+#[tokio::test]
+async fn dropped_messages_leave_sequence_gaps() {
+    let server = TestServer::start_with_args(&[
+        "--ws_loss_rate", "0.5",
+        "--seed", "3",
+    ]);
+    let mut stream = server.connect_ws(WS_SINGLE_ROOM_ROUTE).await;
+
+    let sequences = next_sequences(&mut stream, 20).await;
+
+    assert!(sequences.windows(2).all(|pair| pair[0] < pair[1]), "{:?}", sequences);
+    assert!(sequences.windows(2).any(|pair| pair[1] - pair[0] > 1), "{:?}", sequences);
+}
+
+#[tokio::test]
+async fn nothing_is_dropped_by_default() {
+    let server = TestServer::start();
+    let mut stream = server.connect_ws(WS_SINGLE_ROOM_ROUTE).await;
+
+    let sequences = next_sequences(&mut stream, 10).await;
+
+    assert!(sequences.windows(2).all(|pair| pair[1] == pair[0] + 1), "{:?}", sequences);
+}
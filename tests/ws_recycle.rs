@@ -0,0 +1,148 @@
+mod common;
+
+use common::*;
+use futures_util::StreamExt;
+use std::time::Duration;
+use tokio::net::TcpStream;
+use tokio_tungstenite::{
+    tungstenite::Message,
+    MaybeTlsStream,
+    WebSocketStream,
+};
+
+// The number of streams the server keeps buffered for resuming.
+const MAX_RESUMABLE_STREAMS: usize = 100;
+
+/// The ClosedStream structure records what was sent on a connection
+/// before the server closed it.
+struct ClosedStream {
+    // The sequence numbers of the chat messages.
+    sequences:  Vec<u64>,
+
+    // The last resume token.
+    token:      String,
+
+    // The reason given by a resume_failed frame, if one was sent.
+    failure:    Option<String>,
+
+    code:       u16,
+}
+
+/// This function reads the stream until the server closes it.
+async fn read_until_closed(stream: &mut WebSocketStream<MaybeTlsStream<TcpStream>>) -> ClosedStream {
+    let mut closed = ClosedStream {
+        sequences:  Vec::new(),
+        token:      String::new(),
+        failure:    None,
+        code:       0,
+    };
+
+    loop {
+        let message = tokio::time::timeout(Duration::from_secs(5), stream.next())
+            .await
+            .expect("No message arrived")
+            .unwrap()
+            .unwrap();
+
+        match message {
+            Message::Text(text) => {
+                let body: serde_json::Value = serde_json::from_str(&text).unwrap();
+
+                if body["type"] == "resume" {
+                    closed.token = String::from(body["token"].as_str().unwrap());
+                } else if body["type"] == "resume_failed" {
+                    // It comes before anything else on the stream.
+                    assert!(closed.sequences.is_empty());
+                    closed.failure = Some(String::from(body["reason"].as_str().unwrap()));
+                } else {
+                    closed.sequences.push(body["sequence"].as_u64().unwrap());
+                }
+            }
+            Message::Close(frame) => {
+                closed.code = frame.map_or(0, |frame| u16::from(frame.code));
+                return closed;
+            }
+            _ => {}
+        }
+    }
+}
+
+#[tokio::test]
+async fn recycled_connections_resume_without_gaps() {
+    let server = TestServer::start_with_args(&["--ws_recycle_after", "3-5", "--ws_interval_ms", "50"]);
+
+    let mut stream = server.connect_ws(WS_SINGLE_ROOM_ROUTE).await;
+    let first = read_until_closed(&mut stream).await;
+    assert_eq!(first.code, 1012);
+    assert!((3..=5).contains(&first.sequences.len()), "{:?}", first.sequences);
+
+    let mut stream = server.connect_ws(&format!("{}?resume={}", WS_SINGLE_ROOM_ROUTE, first.token)).await;
+    let resumed = read_until_closed(&mut stream).await;
+    assert_eq!(resumed.code, 1012);
+    assert_eq!(resumed.failure, None);
+
+    let mut sequences = first.sequences;
+    sequences.extend(resumed.sequences);
+    let expected: Vec<u64> = (0..sequences.len() as u64).collect();
+    assert_eq!(sequences, expected);
+}
+
+#[tokio::test]
+async fn altered_tokens_start_a_new_stream() {
+    let server = TestServer::start_with_args(&["--ws_recycle_after", "3", "--ws_interval_ms", "50"]);
+
+    let mut stream = server.connect_ws(WS_SINGLE_ROOM_ROUTE).await;
+    let token = read_until_closed(&mut stream).await.token;
+
+    // Change the last character, which is part of the signature.
+    let mut altered = token.clone();
+    let last = altered.pop().unwrap();
+    altered.push(if last == '0' { '1' } else { '0' });
+
+    let mut stream = server.connect_ws(&format!("{}?resume={}", WS_SINGLE_ROOM_ROUTE, altered)).await;
+    let closed = read_until_closed(&mut stream).await;
+    assert_eq!(closed.failure.as_deref(), Some("The resume token is invalid, so a new stream has started"));
+    assert_eq!(closed.sequences, [0, 1, 2]);
+}
+
+#[tokio::test]
+async fn evicted_streams_start_a_new_stream() {
+    let server = TestServer::start_with_args(&["--ws_recycle_after", "3", "--ws_interval_ms", "50"]);
+
+    let mut stream = server.connect_ws(WS_SINGLE_ROOM_ROUTE).await;
+    let token = read_until_closed(&mut stream).await.token;
+
+    // Open enough other streams to push the first one out of the buffers.
+    let mut others = Vec::new();
+
+    for _ in 0..MAX_RESUMABLE_STREAMS {
+        others.push(server.connect_ws(WS_SINGLE_ROOM_ROUTE).await);
+    }
+    tokio::time::sleep(Duration::from_millis(500)).await;
+
+    let mut stream = server.connect_ws(&format!("{}?resume={}", WS_SINGLE_ROOM_ROUTE, token)).await;
+    let closed = read_until_closed(&mut stream).await;
+    assert_eq!(closed.failure.as_deref(), Some("The stream is no longer buffered, so a new stream has started"));
+    assert_eq!(closed.sequences, [0, 1, 2]);
+}
+
+#[tokio::test]
+async fn search_cursors_are_not_resume_tokens() {
+    let server = TestServer::start_with_args(&["--ws_recycle_after", "3", "--ws_interval_ms", "50"]);
+
+    // Cursors are signed with the same key, but for another purpose.
+    let request = serde_json::json!({
+        "keywordFilter":            { "query": "Antediluvian" },
+        "limit":                    1,
+        "UserHighClassification":   "UNCLASSIFIED",
+    });
+    let response = server.client().post(server.url(SEARCH_MESSAGES_ROUTE))
+        .body(request.to_string())
+        .send().await.unwrap();
+    let body: serde_json::Value = serde_json::from_str(&response.text().await.unwrap()).unwrap();
+    let cursor = body["nextCursorMark"].as_str().unwrap();
+
+    let mut stream = server.connect_ws(&format!("{}?resume={}", WS_SINGLE_ROOM_ROUTE, cursor)).await;
+    let closed = read_until_closed(&mut stream).await;
+    assert_eq!(closed.failure.as_deref(), Some("The resume token is invalid, so a new stream has started"));
+}
@@ -0,0 +1,47 @@
+mod common;
+
+use common::*;
+use tokio_tungstenite::tungstenite::client::IntoClientRequest;
+
+/// This function opens a WebSocket connection requesting the given
+/// subprotocols, returning the one the server chose, if any.
+async fn negotiate(server: &TestServer, requested: &str) -> Option<String> {
+    let mut request = format!("ws://{}{}", server.address, WS_SINGLE_ROOM_ROUTE)
+        .into_client_request()
+        .unwrap();
+    request.headers_mut().insert("Sec-WebSocket-Protocol", requested.parse().unwrap());
+
+    let (_, response) = tokio_tungstenite::connect_async(request).await
+        .expect("Unable to open the WebSocket connection");
+
+    response.headers().get("Sec-WebSocket-Protocol")
+        .map(|value| String::from(value.to_str().unwrap()))
+}
+
+#[tokio::test]
+async fn an_allowed_subprotocol_is_echoed() {
+    // Each negotiation gets its own server, so that one stream can't hold
+    // up the next handshake.
+    for (requested, chosen) in [("chat.v1", "chat.v1"), ("unknown,chat.v2", "chat.v2")] {
+        let server = TestServer::start_with_args(&["--ws_subprotocols", "chat.v2,chat.v1"]);
+        assert_eq!(negotiate(&server, requested).await.as_deref(), Some(chosen));
+    }
+}
+
+#[tokio::test]
+async fn unknown_subprotocols_still_upgrade() {
+    let server = TestServer::start_with_args(&["--ws_subprotocols", "chat.v2"]);
+
+    // tungstenite rightly gives up when none of its subprotocols were
+    // chosen, so make the upgrade request by hand.
+    let response = server.client().get(server.url(WS_SINGLE_ROOM_ROUTE))
+        .header("Connection", "Upgrade")
+        .header("Upgrade", "websocket")
+        .header("Sec-WebSocket-Version", "13")
+        .header("Sec-WebSocket-Key", "dGhlIHNhbXBsZSBub25jZQ==")
+        .header("Sec-WebSocket-Protocol", "unknown")
+        .send().await.unwrap();
+
+    assert_eq!(response.status(), reqwest::StatusCode::SWITCHING_PROTOCOLS);
+    assert!(response.headers().get("Sec-WebSocket-Protocol").is_none());
+}